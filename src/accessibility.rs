@@ -0,0 +1,270 @@
+use bevy::prelude::*;
+use leafwing_input_manager::{plugin::InputManagerSystem, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bullet::{AimSet, CursorPosition},
+    game_state::{despawn_screen, screen_root, screen_text, GameState},
+    prelude::*,
+    replay::ReplayPlayback,
+    user_config::UserConfigFile,
+};
+
+/// Enemies further away than this are never auto-aimed at.
+const AUTO_AIM_RANGE: f32 = 600.;
+
+/// Optional assists for players who can't hold the fire button or aim precisely.
+///
+/// Both work by driving the same [`Action::Shoot`] and [`CursorPosition`] as manual play,
+/// so recorded replays don't depend on the settings.
+pub struct AccessibilityPlugin;
+
+impl Plugin for AccessibilityPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(AccessibilitySettings::default())
+            .init_resource::<AutoAimTarget>()
+            .init_resource::<AccessibilityMenu>()
+            .add_systems(Startup, load_settings)
+            .add_systems(
+                OnEnter(GameState::Accessibility),
+                spawn_accessibility_screen,
+            )
+            .add_systems(
+                OnExit(GameState::Accessibility),
+                despawn_screen::<AccessibilityScreen>,
+            )
+            .add_systems(
+                PreUpdate,
+                auto_fire
+                    .in_set(InputManagerSystem::ManualControl)
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(not(resource_exists::<ReplayPlayback>)),
+            )
+            .add_systems(
+                Update,
+                (
+                    auto_aim
+                        .after(AimSet)
+                        .run_if(in_state(GameState::Playing))
+                        .run_if(not(resource_exists::<ReplayPlayback>)),
+                    (accessibility_screen_input, update_accessibility_screen)
+                        .chain()
+                        .run_if(in_state(GameState::Accessibility)),
+                ),
+            );
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AutoAim {
+    #[default]
+    Off,
+    /// Aims at the closest enemy.
+    Nearest,
+    /// Aims at the enemy with the least health left, to finish off wounded ones first.
+    LowestHealth,
+}
+
+impl AutoAim {
+    fn next(self) -> Self {
+        match self {
+            AutoAim::Off => AutoAim::Nearest,
+            AutoAim::Nearest => AutoAim::LowestHealth,
+            AutoAim::LowestHealth => AutoAim::Off,
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            AutoAim::Off => "Off",
+            AutoAim::Nearest => "Nearest enemy",
+            AutoAim::LowestHealth => "Lowest health enemy",
+        }
+    }
+}
+
+#[derive(Resource, Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AccessibilitySettings {
+    /// Fire on cooldown without holding the shoot button.
+    pub auto_fire: bool,
+    pub auto_aim: AutoAim,
+}
+
+impl UserConfigFile for AccessibilitySettings {
+    const FILE_NAME: &'static str = "accessibility.ron";
+}
+
+fn load_settings(mut settings: ResMut<AccessibilitySettings>) {
+    match AccessibilitySettings::load() {
+        Ok(loaded) => *settings = loaded,
+        Err(err) if err.is_missing_file() => {}
+        Err(err) => warn!("Using default accessibility settings: {err}"),
+    }
+}
+
+fn save_settings(settings: &AccessibilitySettings) {
+    match settings.save() {
+        Ok(path) => info!("Saved accessibility settings to {}", path.display()),
+        Err(err) => error!("Failed to save accessibility settings: {err}"),
+    }
+}
+
+/// Position of the enemy picked by auto-aim this frame, if any is in range.
+#[derive(Resource, Default)]
+struct AutoAimTarget(Option<Vec2>);
+
+fn auto_aim(
+    settings: Res<AccessibilitySettings>,
+    q_enemies: Query<(&Transform, &Health), With<Enemy>>,
+    player_pos: Res<PlayerPosition>,
+    mut cursor_position: ResMut<CursorPosition>,
+    mut target: ResMut<AutoAimTarget>,
+) {
+    let in_range = q_enemies
+        .iter()
+        .map(|(transform, health)| {
+            let position = transform.translation.truncate();
+            (position, position.distance(player_pos.0), health.cur_hp)
+        })
+        .filter(|(_, distance, _)| *distance <= AUTO_AIM_RANGE);
+
+    target.0 = match settings.auto_aim {
+        AutoAim::Off => None,
+        AutoAim::Nearest => in_range
+            .min_by(|(_, a, _), (_, b, _)| a.total_cmp(b))
+            .map(|(position, ..)| position),
+        AutoAim::LowestHealth => in_range
+            .min_by(|(_, dist_a, hp_a), (_, dist_b, hp_b)| {
+                hp_a.total_cmp(hp_b).then(dist_a.total_cmp(dist_b))
+            })
+            .map(|(position, ..)| position),
+    };
+
+    if let Some(position) = target.0 {
+        cursor_position.0 = position;
+    }
+}
+
+fn auto_fire(
+    settings: Res<AccessibilitySettings>,
+    target: Res<AutoAimTarget>,
+    mut q_player: Query<&mut ActionState<Action>, With<Player>>,
+) {
+    // With auto-aim on, only fire when there is something to aim at
+    let has_target = settings.auto_aim == AutoAim::Off || target.0.is_some();
+    if !settings.auto_fire || !has_target {
+        return;
+    }
+
+    if let Ok(mut action_state) = q_player.get_single_mut() {
+        action_state.press(&Action::Shoot);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Setting {
+    AutoFire,
+    AutoAim,
+}
+
+impl Setting {
+    const ALL: [Setting; 2] = [Setting::AutoFire, Setting::AutoAim];
+
+    fn label(&self) -> &'static str {
+        match self {
+            Setting::AutoFire => "Auto-fire",
+            Setting::AutoAim => "Auto-aim",
+        }
+    }
+
+    fn value(&self, settings: &AccessibilitySettings) -> &'static str {
+        match self {
+            Setting::AutoFire => match settings.auto_fire {
+                true => "On",
+                false => "Off",
+            },
+            Setting::AutoAim => settings.auto_aim.label(),
+        }
+    }
+
+    fn toggle(&self, settings: &mut AccessibilitySettings) {
+        match self {
+            Setting::AutoFire => settings.auto_fire = !settings.auto_fire,
+            Setting::AutoAim => settings.auto_aim = settings.auto_aim.next(),
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+struct AccessibilityMenu {
+    selected: usize,
+}
+
+#[derive(Component)]
+struct AccessibilityScreen;
+
+#[derive(Component)]
+struct SettingRow(Setting);
+
+fn spawn_accessibility_screen(mut menu: ResMut<AccessibilityMenu>, mut commands: Commands) {
+    *menu = AccessibilityMenu::default();
+
+    commands
+        .spawn((screen_root(), AccessibilityScreen))
+        .with_children(|children| {
+            children.spawn(screen_text("Accessibility", 48.));
+            Setting::ALL.into_iter().for_each(|setting| {
+                children.spawn((screen_text("", 24.), SettingRow(setting)));
+            });
+            children.spawn(screen_text(
+                "Up/Down - select, Enter - change, Esc - back",
+                18.,
+            ));
+        });
+}
+
+fn accessibility_screen_input(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut menu: ResMut<AccessibilityMenu>,
+    mut settings: ResMut<AccessibilitySettings>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keys.just_pressed(KeyCode::ArrowUp) {
+        menu.selected = (menu.selected + Setting::ALL.len() - 1) % Setting::ALL.len();
+    } else if keys.just_pressed(KeyCode::ArrowDown) {
+        menu.selected = (menu.selected + 1) % Setting::ALL.len();
+    } else if keys.just_pressed(KeyCode::Enter) {
+        Setting::ALL[menu.selected].toggle(&mut settings);
+        save_settings(&settings);
+    } else if keys.just_pressed(KeyCode::Escape) {
+        next_state.set(GameState::MainMenu);
+    }
+}
+
+fn update_accessibility_screen(
+    menu: Res<AccessibilityMenu>,
+    settings: Res<AccessibilitySettings>,
+    mut q_rows: Query<(&mut Text, &SettingRow)>,
+) {
+    if !menu.is_changed() && !settings.is_changed() {
+        return;
+    }
+
+    let selected = Setting::ALL[menu.selected];
+    q_rows
+        .iter_mut()
+        .for_each(|(mut text, SettingRow(setting))| {
+            let section = &mut text.sections[0];
+            let is_selected = *setting == selected;
+            section.value = format!(
+                "{}{}: {}",
+                if is_selected { "> " } else { "" },
+                setting.label(),
+                setting.value(&settings)
+            );
+            section.style.color = match is_selected {
+                true => Color::YELLOW,
+                false => Color::WHITE,
+            };
+        });
+}
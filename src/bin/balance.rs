@@ -0,0 +1,187 @@
+//! Plays many seeded headless runs with a scripted player and reports how they went.
+//!
+//! `cargo run --bin balance -- --runs 32 --duration 300 --format csv > runs.csv`
+
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+    thread,
+};
+
+use bevy::prelude::*;
+use bevy_physics_playground::{
+    cli::arg_value,
+    config::{ConfigAsset, CrumbConfig, EnemyConfig, PlayerConfig},
+    game_state::{GameState, RunTime},
+    headless::kite_nearest_enemy,
+    prelude::*,
+    rng::RunSeed,
+    stats::RunStats,
+    xp_crumbs::PlayerLevel,
+};
+use serde::Serialize;
+
+fn parsed_arg<T: std::str::FromStr>(name: &str, default: T) -> T {
+    arg_value(name)
+        .map(|value| {
+            value
+                .parse()
+                .unwrap_or_else(|_| panic!("Invalid value for {name}: {value}"))
+        })
+        .unwrap_or(default)
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct RunReport {
+    seed: u64,
+    survived: f32,
+    died: bool,
+    kills: u32,
+    level: u32,
+    damage_taken: f32,
+    xp_collected: f32,
+}
+
+struct Configs {
+    player: PlayerConfig,
+    enemy: EnemyConfig,
+    crumbs: CrumbConfig,
+}
+
+impl Configs {
+    fn read(assets_dir: &Path) -> Self {
+        Self {
+            player: PlayerConfig::read_file(assets_dir).expect("Failed to read player config"),
+            enemy: EnemyConfig::read_file(assets_dir).expect("Failed to read enemy config"),
+            crumbs: CrumbConfig::read_file(assets_dir).expect("Failed to read crumb config"),
+        }
+    }
+}
+
+fn simulate_run(seed: u64, duration: f32, configs: &Configs) -> RunReport {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, HeadlessPlugin, SimulationPlugin))
+        .insert_resource(RunSeed(Some(seed)))
+        .insert_resource(configs.player.clone())
+        .insert_resource(configs.enemy.clone())
+        .insert_resource(configs.crumbs.clone())
+        .add_systems(
+            FixedUpdate,
+            kite_nearest_enemy.run_if(in_state(GameState::Playing)),
+        );
+
+    // Every update is one tick, leave some slack for the menus and level ups
+    let max_updates = (duration * 60. * 2.) as u32 + 100;
+    for _ in 0..max_updates {
+        app.update();
+
+        let state = *app.world.resource::<State<GameState>>().get();
+        let survived = app.world.resource::<RunTime>().0.elapsed_secs();
+        if state == GameState::GameOver || survived >= duration {
+            break;
+        }
+    }
+
+    let run_stats = app.world.resource::<RunStats>();
+    RunReport {
+        seed,
+        survived: app.world.resource::<RunTime>().0.elapsed_secs(),
+        died: *app.world.resource::<State<GameState>>().get() == GameState::GameOver,
+        kills: run_stats.kills,
+        level: app.world.resource::<PlayerLevel>().cur_level,
+        damage_taken: run_stats.damage_taken,
+        xp_collected: run_stats.xp_collected,
+    }
+}
+
+fn write_csv(reports: &[RunReport]) {
+    println!("seed,survived,died,kills,level,damage_taken,xp_collected");
+    reports.iter().for_each(|report| {
+        println!(
+            "{},{:.2},{},{},{},{:.1},{:.1}",
+            report.seed,
+            report.survived,
+            report.died,
+            report.kills,
+            report.level,
+            report.damage_taken,
+            report.xp_collected
+        );
+    });
+}
+
+fn print_summary(reports: &[RunReport]) {
+    let mean = |value: fn(&RunReport) -> f32| {
+        reports.iter().map(value).sum::<f32>() / reports.len().max(1) as f32
+    };
+    let deaths = reports.iter().filter(|report| report.died).count();
+
+    eprintln!(
+        "{} runs, {} died ({:.0}%)",
+        reports.len(),
+        deaths,
+        100. * deaths as f32 / reports.len().max(1) as f32
+    );
+    eprintln!("mean survived:     {:.1}s", mean(|report| report.survived));
+    eprintln!(
+        "mean kills:        {:.1}",
+        mean(|report| report.kills as f32)
+    );
+    eprintln!(
+        "mean level:        {:.2}",
+        mean(|report| report.level as f32)
+    );
+    eprintln!(
+        "mean damage taken: {:.1}",
+        mean(|report| report.damage_taken)
+    );
+    eprintln!(
+        "mean xp collected: {:.1}",
+        mean(|report| report.xp_collected)
+    );
+}
+
+fn main() {
+    let runs: u64 = parsed_arg("--runs", 16);
+    let first_seed: u64 = parsed_arg("--seed", 0);
+    let duration: f32 = parsed_arg("--duration", 300.);
+    let threads: usize = parsed_arg(
+        "--threads",
+        thread::available_parallelism().map_or(1, |threads| threads.get()),
+    );
+    let format = arg_value("--format").unwrap_or("csv".into());
+    let assets_dir = PathBuf::from(arg_value("--assets").unwrap_or("assets".into()));
+
+    let configs = Configs::read(&assets_dir);
+    let next_run = AtomicU64::new(0);
+    let reports = Mutex::new(Vec::new());
+
+    thread::scope(|scope| {
+        (0..threads.min(runs as usize)).for_each(|_| {
+            scope.spawn(|| loop {
+                let run = next_run.fetch_add(1, Ordering::Relaxed);
+                if run >= runs {
+                    break;
+                }
+                let report = simulate_run(first_seed + run, duration, &configs);
+                reports.lock().expect("Report lock poisoned").push(report);
+            });
+        });
+    });
+
+    let mut reports = reports.into_inner().expect("Report lock poisoned");
+    reports.sort_by_key(|report| report.seed);
+
+    match format.as_str() {
+        "csv" => write_csv(&reports),
+        "json" => println!(
+            "{}",
+            serde_json::to_string_pretty(&reports).expect("Failed to serialize reports")
+        ),
+        other => panic!("Unknown format {other}, expected csv or json"),
+    }
+    print_summary(&reports);
+}
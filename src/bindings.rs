@@ -0,0 +1,332 @@
+use bevy::prelude::*;
+use leafwing_input_manager::{prelude::*, user_input::InputKind};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game_state::{despawn_screen, screen_root, screen_text, GameState},
+    prelude::*,
+    user_config::UserConfigFile,
+};
+
+/// Loads the player's key bindings from the user config directory and lets them be remapped
+/// from [`GameState::Bindings`].
+pub struct BindingsPlugin;
+
+impl Plugin for BindingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(KeyBindings::default())
+            .init_resource::<BindingsMenu>()
+            .add_systems(Startup, load_bindings)
+            .add_systems(OnEnter(GameState::Bindings), spawn_bindings_screen)
+            .add_systems(
+                OnExit(GameState::Bindings),
+                despawn_screen::<BindingsScreen>,
+            )
+            .add_systems(
+                Update,
+                (
+                    apply_bindings.run_if(resource_changed::<KeyBindings>),
+                    (bindings_screen_input, update_bindings_screen)
+                        .chain()
+                        .run_if(in_state(GameState::Bindings)),
+                ),
+            );
+    }
+}
+
+/// Keyboard and mouse bindings. Gamepad bindings always use [`Action::default_gamepad_map`].
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeyBindings {
+    pub move_up: InputKind,
+    pub move_down: InputKind,
+    pub move_left: InputKind,
+    pub move_right: InputKind,
+    pub dash: InputKind,
+    pub shoot: InputKind,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            move_up: InputKind::PhysicalKey(KeyCode::KeyW),
+            move_down: InputKind::PhysicalKey(KeyCode::KeyS),
+            move_left: InputKind::PhysicalKey(KeyCode::KeyA),
+            move_right: InputKind::PhysicalKey(KeyCode::KeyD),
+            dash: InputKind::PhysicalKey(KeyCode::ShiftLeft),
+            shoot: InputKind::Mouse(MouseButton::Left),
+        }
+    }
+}
+
+impl KeyBindings {
+    pub fn input_map(&self) -> InputMap<Action> {
+        let mut input_map = InputMap::default();
+
+        input_map.insert(
+            Action::Move,
+            VirtualDPad {
+                up: self.move_up,
+                down: self.move_down,
+                left: self.move_left,
+                right: self.move_right,
+            },
+        );
+        input_map.insert(Action::Dash, self.dash);
+        input_map.insert(Action::Shoot, self.shoot);
+
+        input_map.merge(&Action::default_gamepad_map());
+        input_map
+    }
+
+    pub fn get(&self, binding: Binding) -> InputKind {
+        match binding {
+            Binding::MoveUp => self.move_up,
+            Binding::MoveDown => self.move_down,
+            Binding::MoveLeft => self.move_left,
+            Binding::MoveRight => self.move_right,
+            Binding::Dash => self.dash,
+            Binding::Shoot => self.shoot,
+        }
+    }
+
+    pub fn set(&mut self, binding: Binding, input: InputKind) {
+        let slot = match binding {
+            Binding::MoveUp => &mut self.move_up,
+            Binding::MoveDown => &mut self.move_down,
+            Binding::MoveLeft => &mut self.move_left,
+            Binding::MoveRight => &mut self.move_right,
+            Binding::Dash => &mut self.dash,
+            Binding::Shoot => &mut self.shoot,
+        };
+        *slot = input;
+    }
+
+    /// Another binding that already uses `input`.
+    pub fn conflict(&self, binding: Binding, input: InputKind) -> Option<Binding> {
+        Binding::ALL
+            .into_iter()
+            .find(|other| *other != binding && self.get(*other) == input)
+    }
+
+    /// Binds `input` unless another binding already uses it, returning that one instead.
+    pub fn rebind(&mut self, binding: Binding, input: InputKind) -> Result<(), Binding> {
+        match self.conflict(binding, input) {
+            Some(other) => Err(other),
+            None => {
+                self.set(binding, input);
+                Ok(())
+            }
+        }
+    }
+}
+
+impl UserConfigFile for KeyBindings {
+    const FILE_NAME: &'static str = "bindings.ron";
+}
+
+/// A single remappable input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Binding {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Dash,
+    Shoot,
+}
+
+impl Binding {
+    pub const ALL: [Binding; 6] = [
+        Binding::MoveUp,
+        Binding::MoveDown,
+        Binding::MoveLeft,
+        Binding::MoveRight,
+        Binding::Dash,
+        Binding::Shoot,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Binding::MoveUp => "Move up",
+            Binding::MoveDown => "Move down",
+            Binding::MoveLeft => "Move left",
+            Binding::MoveRight => "Move right",
+            Binding::Dash => "Dash",
+            Binding::Shoot => "Shoot",
+        }
+    }
+}
+
+fn input_label(input: InputKind) -> String {
+    match input {
+        InputKind::PhysicalKey(key) => format!("{key:?}"),
+        InputKind::Mouse(button) => format!("Mouse {button:?}"),
+        other => format!("{other:?}"),
+    }
+}
+
+fn load_bindings(mut bindings: ResMut<KeyBindings>) {
+    match KeyBindings::load() {
+        Ok(loaded) => *bindings = loaded,
+        Err(err) if err.is_missing_file() => {}
+        Err(err) => warn!("Using default bindings: {err}"),
+    }
+}
+
+fn save_bindings(bindings: &KeyBindings) {
+    match bindings.save() {
+        Ok(path) => info!("Saved bindings to {}", path.display()),
+        Err(err) => error!("Failed to save bindings: {err}"),
+    }
+}
+
+fn apply_bindings(
+    bindings: Res<KeyBindings>,
+    mut q_player: Query<&mut InputMap<Action>, With<Player>>,
+) {
+    q_player.iter_mut().for_each(|mut input_map| {
+        *input_map = bindings.input_map();
+    });
+}
+
+/// Selection on the bindings screen.
+#[derive(Resource, Default)]
+struct BindingsMenu {
+    selected: usize,
+    /// Waiting for the next press to bind to the selected entry.
+    capturing: bool,
+    message: Option<String>,
+}
+
+#[derive(Component)]
+struct BindingsScreen;
+
+#[derive(Component)]
+struct BindingRow(Binding);
+
+#[derive(Component)]
+struct BindingsMessage;
+
+fn spawn_bindings_screen(mut menu: ResMut<BindingsMenu>, mut commands: Commands) {
+    *menu = BindingsMenu::default();
+
+    commands
+        .spawn((screen_root(), BindingsScreen))
+        .with_children(|children| {
+            children.spawn(screen_text("Key Bindings", 48.));
+            Binding::ALL.into_iter().for_each(|binding| {
+                children.spawn((screen_text("", 24.), BindingRow(binding)));
+            });
+            children.spawn((screen_text("", 20.), BindingsMessage));
+            children.spawn(screen_text(
+                "Up/Down - select, Enter - rebind, R - reset to defaults, Esc - back",
+                18.,
+            ));
+        });
+}
+
+fn bindings_screen_input(
+    keys: Res<ButtonInput<KeyCode>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut menu: ResMut<BindingsMenu>,
+    mut bindings: ResMut<KeyBindings>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let selected = Binding::ALL[menu.selected];
+
+    if menu.capturing {
+        if keys.just_pressed(KeyCode::Escape) {
+            menu.capturing = false;
+            menu.message = None;
+            return;
+        }
+
+        let Some(input) = keys
+            .get_just_pressed()
+            .next()
+            .map(|key| InputKind::PhysicalKey(*key))
+            .or_else(|| {
+                mouse
+                    .get_just_pressed()
+                    .next()
+                    .map(|b| InputKind::Mouse(*b))
+            })
+        else {
+            return;
+        };
+
+        match bindings.rebind(selected, input) {
+            Err(other) => {
+                menu.message = Some(format!(
+                    "{} is already bound to {}",
+                    input_label(input),
+                    other.label()
+                ));
+            }
+            Ok(()) => {
+                save_bindings(&bindings);
+                menu.capturing = false;
+                menu.message = None;
+            }
+        }
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::ArrowUp) {
+        menu.selected = (menu.selected + Binding::ALL.len() - 1) % Binding::ALL.len();
+    } else if keys.just_pressed(KeyCode::ArrowDown) {
+        menu.selected = (menu.selected + 1) % Binding::ALL.len();
+    } else if keys.just_pressed(KeyCode::Enter) {
+        menu.capturing = true;
+        menu.message = Some(format!(
+            "Press a key or mouse button for {}, Esc to cancel",
+            selected.label()
+        ));
+    } else if keys.just_pressed(KeyCode::KeyR) {
+        *bindings = KeyBindings::default();
+        save_bindings(&bindings);
+        menu.message = Some("Reset to defaults".into());
+    } else if keys.just_pressed(KeyCode::Escape) {
+        next_state.set(GameState::MainMenu);
+    }
+}
+
+fn update_bindings_screen(
+    menu: Res<BindingsMenu>,
+    bindings: Res<KeyBindings>,
+    mut q_rows: Query<(&mut Text, &BindingRow), Without<BindingsMessage>>,
+    mut q_message: Query<&mut Text, With<BindingsMessage>>,
+) {
+    if !menu.is_changed() && !bindings.is_changed() {
+        return;
+    }
+
+    let selected = Binding::ALL[menu.selected];
+    q_rows
+        .iter_mut()
+        .for_each(|(mut text, BindingRow(binding))| {
+            let section = &mut text.sections[0];
+            let is_selected = *binding == selected;
+            section.value = match (is_selected, menu.capturing) {
+                (true, true) => format!("> {}: ...", binding.label()),
+                (true, false) => format!(
+                    "> {}: {}",
+                    binding.label(),
+                    input_label(bindings.get(*binding))
+                ),
+                (false, _) => format!(
+                    "{}: {}",
+                    binding.label(),
+                    input_label(bindings.get(*binding))
+                ),
+            };
+            section.style.color = match is_selected {
+                true => Color::YELLOW,
+                false => Color::WHITE,
+            };
+        });
+
+    if let Ok(mut text) = q_message.get_single_mut() {
+        text.sections[0].value = menu.message.clone().unwrap_or_default();
+    }
+}
@@ -1,107 +1,119 @@
 use bevy::prelude::*;
 use std::time::Duration;
 
-use crate::enemy::Invulnerable;
+use crate::game_state::GameState;
 use bevy::ecs::system::EntityCommand;
 
 pub struct BlinkPlugin;
 
 impl Plugin for BlinkPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, blink_system);
+        app.add_systems(
+            FixedUpdate,
+            blink_system.run_if(in_state(GameState::Playing)),
+        );
     }
 }
 
-impl EntityCommand for GoInvulnerable {
-    fn apply(self, entity: Entity, world: &mut World) {
-        dbg!("I'm invulnerable!");
-        world
-            .entity_mut(entity)
-            .insert(BlinkBundle::new(self.duration, self.blink_frequency))
-            .insert(Invulnerable);
-    }
-}
-
-pub struct GoInvulnerable {
+/// Makes the entity's sprite blink `frequency` times over `duration`, purely as a visual.
+pub struct StartBlinking {
     pub(crate) duration: Duration,
-    pub(crate) blink_frequency: u32,
+    pub(crate) frequency: u32,
 }
 
-impl Default for GoInvulnerable {
+impl Default for StartBlinking {
     fn default() -> Self {
         Self {
             duration: Duration::from_secs_f32(2.),
-            blink_frequency: 10,
+            frequency: 10,
         }
     }
 }
 
-#[allow(dead_code)]
-impl GoInvulnerable {
-    pub(crate) fn new(duration: f32, frequency: u32) -> Self {
+impl StartBlinking {
+    pub fn new(duration: f32, frequency: u32) -> Self {
         Self {
             duration: Duration::from_secs_f32(duration),
-            blink_frequency: frequency,
+            frequency,
         }
     }
 }
 
-pub struct StopInvulnerability;
+impl EntityCommand for StartBlinking {
+    fn apply(self, id: Entity, world: &mut World) {
+        let Some(mut entity) = world.get_entity_mut(id) else {
+            return;
+        };
+        // Blinking again mid-blink must not record the hidden alpha as the original one
+        let alpha = match (entity.get::<Blink>(), entity.get::<Sprite>()) {
+            (Some(blink), _) => blink.alpha,
+            (None, Some(sprite)) => sprite.color.a(),
+            (None, None) => return,
+        };
 
-impl EntityCommand for StopInvulnerability {
+        entity.insert(Blink::new(self.duration, self.frequency, alpha));
+    }
+}
+
+pub struct StopBlinking;
+
+impl EntityCommand for StopBlinking {
     fn apply(self, id: Entity, world: &mut World) {
-        dbg!("Uh-oh!");
-        if let Some(mut world_mut) = world.get_entity_mut(id) {
-            world_mut.remove::<BlinkBundle>().remove::<Invulnerable>();
+        let Some(mut entity) = world.get_entity_mut(id) else {
+            return;
+        };
+        let Some(blink) = entity.take::<Blink>() else {
+            return;
+        };
+        if let Some(mut sprite) = entity.get_mut::<Sprite>() {
+            sprite.color.set_a(blink.alpha);
         }
     }
 }
 
+/// Toggles the sprite between hidden and its original alpha until no toggles are left.
 #[derive(Component)]
-pub struct BlinkTimer(Timer);
-
-#[derive(Component)]
-pub struct BlinkCount(u32);
-
-#[derive(Bundle)]
-pub struct BlinkBundle {
-    pub(crate) blink_timer: BlinkTimer,
-    pub(crate) blinks_left: BlinkCount,
+pub struct Blink {
+    timer: Timer,
+    toggles_left: u32,
+    /// Alpha the sprite had before it started blinking.
+    alpha: f32,
+    hidden: bool,
 }
 
-impl BlinkBundle {
-    pub(crate) fn new(duration: Duration, frequency: u32) -> Self {
-        let blink_timer = BlinkTimer(Timer::new(duration / (frequency * 2), TimerMode::Once));
-        let blinks_left = BlinkCount(frequency * 2);
+impl Blink {
+    fn new(duration: Duration, frequency: u32, alpha: f32) -> Self {
+        let toggles = (frequency * 2).max(1);
         Self {
-            blink_timer,
-            blinks_left,
+            timer: Timer::new(duration / toggles, TimerMode::Repeating),
+            toggles_left: toggles,
+            alpha,
+            hidden: false,
         }
     }
 }
 
 pub(crate) fn blink_system(
     time: Res<Time>,
-    mut q_blinking: Query<(Entity, &mut BlinkTimer, &mut BlinkCount, &mut Sprite)>,
+    mut q_blinking: Query<(Entity, &mut Blink, &mut Sprite)>,
     mut commands: Commands,
 ) {
     q_blinking
         .iter_mut()
-        .for_each(|(entity, mut blink_timer, mut blink_count, mut sprite)| {
-            if blink_timer.0.tick(time.delta()).finished() {
-                let new_opacity = match sprite.color.a() {
-                    0. => 1.,
-                    1. => 0.,
-                    val => todo!("Blinking for opaque entities not implemented"),
-                };
-                sprite.color.set_a(new_opacity);
-                blink_count.0 -= 1;
-                blink_timer.0.reset();
+        .for_each(|(entity, mut blink, mut sprite)| {
+            let toggles = blink.timer.tick(time.delta()).times_finished_this_tick();
+            (0..toggles.min(blink.toggles_left)).for_each(|_| {
+                blink.hidden = !blink.hidden;
+                blink.toggles_left -= 1;
+            });
 
-                if blink_count.0 == 0 {
-                    commands.entity(entity).add(StopInvulnerability);
-                    sprite.color.set_a(1.);
-                }
+            if blink.toggles_left == 0 {
+                commands.entity(entity).add(StopBlinking);
+                return;
             }
+            sprite.color.set_a(match blink.hidden {
+                true => 0.,
+                false => blink.alpha,
+            });
         });
 }
@@ -0,0 +1,333 @@
+use std::{
+    fmt,
+    io::{self, BufRead, BufReader, Write},
+    net::{TcpListener, ToSocketAddrs},
+};
+
+use bevy::{app::AppExit, prelude::*};
+use bevy_xpbd_2d::prelude::*;
+use leafwing_input_manager::plugin::InputManagerSystem;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    enemy::KeepDistance,
+    game_state::{GameState, RunTime},
+    headless::ScriptedInput,
+    prelude::*,
+    rng::GameRng,
+    xp_crumbs::{Crumb, HealingCrumb, MagnetCrumb, PlayerLevel},
+};
+
+/// Only enemies and crumbs within this distance of the player are observed.
+const OBSERVATION_RADIUS: f32 = 1000.;
+
+/// Lets an external agent play through a [`BotConnection`], on top of [`HeadlessPlugin`].
+///
+/// Every tick spent in [`GameState::Playing`] the game sends an [`Observation`] and waits for
+/// a [`BotCommand`] before simulating the tick. Once the run is over a [`RunSummary`] is sent
+/// and the app exits.
+pub struct BotPlugin;
+
+impl Plugin for BotPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PreUpdate,
+            exchange_with_bot
+                .before(InputManagerSystem::ManualControl)
+                .run_if(in_state(GameState::Playing))
+                .run_if(resource_exists::<BotConnection>),
+        )
+        .add_systems(
+            OnEnter(GameState::GameOver),
+            finish_bot_run.run_if(resource_exists::<BotConnection>),
+        );
+    }
+}
+
+/// A line-delimited JSON channel to the agent.
+#[derive(Resource)]
+pub struct BotConnection {
+    reader: Box<dyn BufRead + Send + Sync>,
+    writer: Box<dyn Write + Send + Sync>,
+    tick: u64,
+}
+
+impl BotConnection {
+    pub fn new(
+        reader: impl BufRead + Send + Sync + 'static,
+        writer: impl Write + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            reader: Box::new(reader),
+            writer: Box::new(writer),
+            tick: 0,
+        }
+    }
+
+    /// Talks to the agent over stdin and stdout.
+    pub fn stdio() -> Self {
+        Self::new(BufReader::new(io::stdin()), io::stdout())
+    }
+
+    /// Waits for a single agent to connect on `addr`.
+    pub fn tcp(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let (stream, _) = TcpListener::bind(addr)?.accept()?;
+        stream.set_nodelay(true)?;
+        Ok(Self::new(BufReader::new(stream.try_clone()?), stream))
+    }
+
+    fn send(&mut self, message: &BotMessage) -> Result<(), BotError> {
+        serde_json::to_writer(&mut self.writer, message)?;
+        self.writer.write_all(b"\n")?;
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    /// Blocks until the agent answers with a valid command, reporting malformed ones back to it.
+    fn receive(&mut self) -> Result<BotCommand, BotError> {
+        loop {
+            let mut line = String::new();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Err(BotError::Disconnected);
+            }
+            match serde_json::from_str(&line) {
+                Ok(command) => return Ok(command),
+                Err(err) => self.send(&BotMessage::Error {
+                    message: err.to_string(),
+                })?,
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum BotError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    Disconnected,
+}
+
+impl fmt::Display for BotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BotError::Io(err) => write!(f, "could not talk to bot: {err}"),
+            BotError::Json(err) => write!(f, "could not serialize message: {err}"),
+            BotError::Disconnected => write!(f, "bot disconnected"),
+        }
+    }
+}
+
+impl std::error::Error for BotError {}
+
+impl From<io::Error> for BotError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for BotError {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Json(err)
+    }
+}
+
+/// Everything sent to the agent, tagged with a `type` field.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BotMessage {
+    Observation(Observation),
+    GameOver(RunSummary),
+    Error { message: String },
+}
+
+/// The state of the run at the start of a tick.
+#[derive(Debug, Clone, Serialize)]
+pub struct Observation {
+    pub tick: u64,
+    pub player: PlayerObservation,
+    pub level: u32,
+    pub xp: f32,
+    pub next_level_threshold: f32,
+    /// Enemies within [`OBSERVATION_RADIUS`] of the player.
+    pub enemies: Vec<EnemyObservation>,
+    /// Crumbs within [`OBSERVATION_RADIUS`] of the player.
+    pub crumbs: Vec<CrumbObservation>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlayerObservation {
+    pub position: Vec2,
+    pub velocity: Vec2,
+    pub hp: f32,
+    pub max_hp: f32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EnemyObservation {
+    pub position: Vec2,
+    pub velocity: Vec2,
+    pub hp: f32,
+    pub ranged: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CrumbObservation {
+    pub position: Vec2,
+    pub healing: bool,
+    pub magnet: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RunSummary {
+    pub seed: u64,
+    pub ticks: u64,
+    pub survived: f32,
+    pub level: u32,
+}
+
+/// The agent's input for the next tick. Missing fields fall back to doing nothing.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct BotCommand {
+    /// Held actions. `Move` uses `movement` as its direction.
+    pub actions: Vec<Action>,
+    pub movement: Vec2,
+    /// Direction to shoot in, relative to the player.
+    pub aim: Vec2,
+    /// Index of the upgrade to pick on the next level up.
+    pub upgrade: usize,
+}
+
+impl From<BotCommand> for ScriptedInput {
+    fn from(command: BotCommand) -> Self {
+        let held = |action: Action| command.actions.contains(&action);
+        Self {
+            movement: match held(Action::Move) {
+                true => command.movement,
+                false => Vec2::ZERO,
+            },
+            dash: held(Action::Dash),
+            shoot: held(Action::Shoot),
+            aim: command.aim,
+            upgrade: command.upgrade,
+        }
+    }
+}
+
+type ObservedPlayer<'w, 's> =
+    Query<'w, 's, (&'static Transform, &'static LinearVelocity, &'static Health), With<Player>>;
+type ObservedEnemies<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Transform,
+        &'static LinearVelocity,
+        &'static Health,
+        Has<KeepDistance>,
+    ),
+    With<Enemy>,
+>;
+type ObservedCrumbs<'w, 's> =
+    Query<'w, 's, (&'static Transform, Has<HealingCrumb>, Has<MagnetCrumb>), With<Crumb>>;
+
+fn observe(
+    tick: u64,
+    q_player: &ObservedPlayer,
+    q_enemies: &ObservedEnemies,
+    q_crumbs: &ObservedCrumbs,
+    player_level: &PlayerLevel,
+) -> Option<Observation> {
+    let (player_tr, player_velocity, player_health) = q_player.get_single().ok()?;
+    let player_pos = player_tr.translation.truncate();
+    let nearby = |transform: &Transform| {
+        transform.translation.truncate().distance(player_pos) <= OBSERVATION_RADIUS
+    };
+
+    let enemies = q_enemies
+        .iter()
+        .filter(|(transform, ..)| nearby(transform))
+        .map(|(transform, velocity, health, ranged)| EnemyObservation {
+            position: transform.translation.truncate(),
+            velocity: velocity.0,
+            hp: health.cur_hp,
+            ranged,
+        })
+        .collect();
+    let crumbs = q_crumbs
+        .iter()
+        .filter(|(transform, ..)| nearby(transform))
+        .map(|(transform, healing, magnet)| CrumbObservation {
+            position: transform.translation.truncate(),
+            healing,
+            magnet,
+        })
+        .collect();
+
+    Some(Observation {
+        tick,
+        player: PlayerObservation {
+            position: player_pos,
+            velocity: player_velocity.0,
+            hp: player_health.cur_hp,
+            max_hp: player_health.max_hp,
+        },
+        level: player_level.cur_level,
+        xp: player_level.cur_xp,
+        next_level_threshold: player_level.next_level_threshold,
+        enemies,
+        crumbs,
+    })
+}
+
+fn exchange_with_bot(
+    q_player: ObservedPlayer,
+    q_enemies: ObservedEnemies,
+    q_crumbs: ObservedCrumbs,
+    player_level: Res<PlayerLevel>,
+    mut connection: ResMut<BotConnection>,
+    mut script: ResMut<ScriptedInput>,
+    mut ev_writer: EventWriter<AppExit>,
+) {
+    let Some(observation) = observe(
+        connection.tick,
+        &q_player,
+        &q_enemies,
+        &q_crumbs,
+        &player_level,
+    ) else {
+        return;
+    };
+
+    let command = connection
+        .send(&BotMessage::Observation(observation))
+        .and_then(|_| connection.receive());
+    match command {
+        Ok(command) => {
+            *script = command.into();
+            connection.tick += 1;
+        }
+        Err(err) => {
+            error!("Stopping bot run: {err}");
+            ev_writer.send(AppExit);
+        }
+    }
+}
+
+fn finish_bot_run(
+    run_time: Res<RunTime>,
+    player_level: Res<PlayerLevel>,
+    game_rng: Res<GameRng>,
+    mut connection: ResMut<BotConnection>,
+    mut ev_writer: EventWriter<AppExit>,
+) {
+    let summary = RunSummary {
+        seed: game_rng.seed(),
+        ticks: connection.tick,
+        survived: run_time.0.elapsed_secs(),
+        level: player_level.cur_level,
+    };
+    if let Err(err) = connection.send(&BotMessage::GameOver(summary)) {
+        error!("Failed to report run to bot: {err}");
+    }
+    ev_writer.send(AppExit);
+}
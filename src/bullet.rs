@@ -1,14 +1,17 @@
 use std::fmt::Debug;
 use std::marker::PhantomData;
 
+use crate::{
+    game_state::GameState,
+    prelude::*,
+    replay::ReplayPlayback,
+    status_effects::StatusEffect,
+    weapon::{fire_enemy_weapons, fire_weapons, ProjectileSprite},
+};
 use bevy::{prelude::*, window::PrimaryWindow};
 use bevy_xpbd_2d::{math::AdjustPrecision, prelude::*};
 use leafwing_input_manager::prelude::*;
 
-use rand::Rng;
-
-use crate::prelude::*;
-
 pub struct BulletPlugin;
 
 impl Plugin for BulletPlugin {
@@ -16,12 +19,25 @@ impl Plugin for BulletPlugin {
         app.add_event::<ProjectileHitEvent<Enemy>>()
             .add_event::<ProjectileHitEvent<Player>>()
             .insert_resource(CursorPosition::default())
+            .insert_resource(AimDevice::Mouse)
             .add_event::<ShootEvent>()
             .add_systems(
                 Update,
                 (
-                    cursor_position_system,
-                    shoot_input,
+                    detect_aim_device,
+                    cursor_position_system.run_if(resource_equals(AimDevice::Mouse)),
+                    gamepad_aim_system.run_if(resource_equals(AimDevice::Gamepad)),
+                )
+                    .chain()
+                    .in_set(AimSet)
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(not(resource_exists::<ReplayPlayback>)),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    fire_weapons,
+                    fire_enemy_weapons,
                     bullet_spawner,
                     move_bullets,
                     emit_projectile_hits::<Enemy>,
@@ -29,7 +45,8 @@ impl Plugin for BulletPlugin {
                     tick_shot,
                     expire_bullets,
                 )
-                    .chain(),
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
             );
     }
 }
@@ -54,10 +71,10 @@ pub struct ProjectileBundle {
 }
 
 #[derive(Component, Debug, Clone, Copy)]
-pub struct ShotDirection(Vec2);
+pub struct ShotDirection(pub Vec2);
 
 #[derive(Component, Clone, Copy)]
-pub struct ShotLocation(Vec2);
+pub struct ShotLocation(pub Vec2);
 
 #[derive(Component, Debug)]
 pub struct BulletLifetimeTimer(Timer);
@@ -67,8 +84,10 @@ pub enum GameLayer {
     Player,
     Enemy,
     Bullet,
+    EnemyBullet,
     XpCrumb,
     HealingCrumb,
+    MagnetCrumb,
 }
 
 impl ProjectileBundle {
@@ -78,13 +97,14 @@ impl ProjectileBundle {
         shot_direction: ShotDirection,
         collision_layers: CollisionLayers,
         damage: ProjectileDamage,
+        lifetime: f32,
     ) -> Self {
         Self {
             projectile: Projectile,
             collider,
             shot_direction,
-            movement: MovementBundle::new(acceleration.0, 1.),
-            lifetime: BulletLifetimeTimer(Timer::from_seconds(4., TimerMode::Once)),
+            movement: MovementBundle::new(acceleration.0, 0.),
+            lifetime: BulletLifetimeTimer(Timer::from_seconds(lifetime, TimerMode::Once)),
             rigid_body: RigidBody::Dynamic,
             sensor: Sensor,
             collision_layers,
@@ -94,25 +114,108 @@ impl ProjectileBundle {
 }
 
 #[derive(Component)]
-pub struct ShootCooldown(Timer);
+pub struct ShootCooldown(pub Timer);
 
-impl Default for ShootCooldown {
-    fn default() -> Self {
-        Self(Timer::from_seconds(0.25, TimerMode::Once))
+impl ShootCooldown {
+    /// A cooldown of `seconds` that allows shooting right away.
+    pub fn ready(seconds: f32) -> Self {
+        let mut timer = Timer::from_seconds(seconds, TimerMode::Once);
+        timer.tick(timer.duration());
+        Self(timer)
     }
 }
 
 #[derive(Event)]
 pub struct ShootEvent {
-    shot_location: ShotLocation,
-    shot_direction: ShotDirection,
-    acceleration: MovementAcceleration,
-    collision_layers: CollisionLayers,
-    damage: ProjectileDamage,
+    pub shot_location: ShotLocation,
+    pub shot_direction: ShotDirection,
+    pub acceleration: MovementAcceleration,
+    pub collision_layers: CollisionLayers,
+    pub damage: ProjectileDamage,
+    pub knockback: f32,
+    pub lifetime: f32,
+    pub sprite: ProjectileSprite,
+    pub on_hit: Option<StatusEffect>,
+    pub crit_chance: f32,
 }
 
+/// Strength of the [`Knockback`](crate::knockback::Knockback) a projectile gives to what it hits.
+#[derive(Component, Clone, Copy)]
+pub struct ProjectileKnockback(pub f32);
+
+/// Status effect a projectile applies to what it hits.
+#[derive(Component, Clone, Copy)]
+pub struct OnHitEffect(pub StatusEffect);
+
+/// Chance of a projectile's hit being critical, see [`Weapon::crit_chance`](crate::weapon::Weapon::crit_chance).
+#[derive(Component, Clone, Copy)]
+pub struct CritChance(pub f32);
+
+/// Systems that update [`CursorPosition`] from the mouse or gamepad.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AimSet;
+
+/// World position the player aims at.
 #[derive(Resource, Default)]
-struct CursorPosition(Vec2);
+pub struct CursorPosition(pub Vec2);
+
+/// Distance from the player at which the aim point is placed when aiming with a stick.
+const GAMEPAD_AIM_DISTANCE: f32 = 200.;
+
+/// Which device the player last aimed with. Moving the mouse or the right stick switches to it.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AimDevice {
+    Mouse,
+    Gamepad,
+}
+
+fn detect_aim_device(
+    q_player: Query<&ActionState<Action>, With<Player>>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
+    mut aim_device: ResMut<AimDevice>,
+    mut last_cursor: Local<Option<Vec2>>,
+) {
+    let stick_aiming = q_player
+        .get_single()
+        .ok()
+        .and_then(|action_state| action_state.axis_pair(&Action::Aim))
+        .is_some_and(|aim| aim.xy() != Vec2::ZERO);
+
+    let cursor = q_window
+        .get_single()
+        .ok()
+        .and_then(|window| window.cursor_position());
+    let mouse_moved = cursor.is_some() && cursor != *last_cursor;
+    *last_cursor = cursor;
+
+    let device = match (stick_aiming, mouse_moved) {
+        (true, _) => AimDevice::Gamepad,
+        (false, true) => AimDevice::Mouse,
+        (false, false) => *aim_device,
+    };
+    aim_device.set_if_neq(device);
+}
+
+fn gamepad_aim_system(
+    q_player: Query<&ActionState<Action>, With<Player>>,
+    player_pos: Res<PlayerPosition>,
+    mut cursor_position: ResMut<CursorPosition>,
+    mut aim_direction: Local<Option<Vec2>>,
+) {
+    if let Some(aim) = q_player
+        .get_single()
+        .ok()
+        .and_then(|action_state| action_state.axis_pair(&Action::Aim))
+        .map(|aim| aim.xy().normalize_or_zero())
+        .filter(|aim| *aim != Vec2::ZERO)
+    {
+        *aim_direction = Some(aim);
+    }
+
+    // Keep aiming the same way after the stick is released
+    let aim = aim_direction.unwrap_or(Vec2::X);
+    cursor_position.0 = player_pos.0 + aim * GAMEPAD_AIM_DISTANCE;
+}
 
 fn cursor_position_system(
     mut cursor_position: ResMut<CursorPosition>,
@@ -132,50 +235,9 @@ fn cursor_position_system(
     }
 }
 
-fn shoot_input(
-    query: Query<
-        (
-            &ActionState<Action>,
-            Option<&ShootCooldown>,
-            &Transform,
-            Entity,
-        ),
-        With<Player>,
-    >,
-    cursor_position: Res<CursorPosition>,
-    mut commands: Commands,
-    mut ev_writer: EventWriter<ShootEvent>,
-) {
-    if let Ok((action_state, shoot_cooldown, transform, entity)) = query.get_single() {
-        let shot_direction = cursor_position.0 - transform.translation.truncate();
-        let accuracy_angle = std::f32::consts::PI / 36.;
-        let shot_displace = rand::thread_rng().gen_range(-accuracy_angle..accuracy_angle);
-        let displaced_direction = Quat::from_rotation_z(shot_displace)
-            .mul_vec3(shot_direction.extend(0.))
-            .truncate();
-        if action_state.pressed(&Action::Shoot) && shoot_cooldown.is_none() {
-            ev_writer.send(ShootEvent {
-                shot_location: ShotLocation(transform.translation.xy()),
-                shot_direction: ShotDirection(displaced_direction.normalize()),
-                collision_layers: CollisionLayers::new(GameLayer::Bullet, [GameLayer::Enemy]),
-                acceleration: MovementAcceleration(30000.),
-                damage: ProjectileDamage(5.),
-            });
-
-            commands.entity(entity).insert(ShootCooldown::default());
-        }
-    }
-}
-
-fn tick_shot(
-    time: Res<Time>,
-    mut query: Query<(&mut ShootCooldown, Entity)>,
-    mut commands: Commands,
-) {
-    query.iter_mut().for_each(|(mut cooldown, entity)| {
-        if cooldown.0.tick(time.delta()).finished() {
-            commands.entity(entity).remove::<ShootCooldown>();
-        }
+fn tick_shot(time: Res<Time>, mut query: Query<&mut ShootCooldown>) {
+    query.iter_mut().for_each(|mut cooldown| {
+        cooldown.0.tick(time.delta());
     })
 }
 
@@ -215,19 +277,25 @@ fn bullet_spawner(mut ev_reader: EventReader<ShootEvent>, mut commands: Commands
              collision_layers,
              acceleration,
              damage,
+             knockback,
+             lifetime,
+             sprite,
+             on_hit,
+             crit_chance,
          }| {
             let bullet = ProjectileBundle::new(
                 *acceleration,
-                Collider::rectangle(5., 5.),
+                Collider::rectangle(sprite.size + 1., sprite.size + 1.),
                 *shot_direction,
                 *collision_layers,
                 *damage,
+                *lifetime,
             );
-            commands.spawn((
+            let mut projectile = commands.spawn((
                 SpriteBundle {
                     sprite: Sprite {
-                        color: Color::YELLOW,
-                        custom_size: Some(Vec2::splat(4.)),
+                        color: sprite.color,
+                        custom_size: Some(Vec2::splat(sprite.size)),
                         ..Default::default()
                     },
                     transform: Transform::from_xyz(shot_location.0.x, shot_location.0.y, 0.)
@@ -238,7 +306,14 @@ fn bullet_spawner(mut ev_reader: EventReader<ShootEvent>, mut commands: Commands
                     ..Default::default()
                 },
                 bullet,
+                ProjectileKnockback(*knockback),
             ));
+            if let Some(effect) = on_hit {
+                projectile.insert(OnHitEffect(*effect));
+            }
+            if *crit_chance > 0. {
+                projectile.insert(CritChance(*crit_chance));
+            }
         },
     )
 }
@@ -262,13 +337,16 @@ impl<T: Component + Debug> ProjectileHitEvent<T> {
 
 fn emit_projectile_hits<T: Component + Debug>(
     hit_targets: Query<(Entity, &CollidingEntities), With<Projectile>>,
+    q_targets: Query<(), With<T>>,
     mut ev_writer: EventWriter<ProjectileHitEvent<T>>,
 ) {
     hit_targets.iter().for_each(|(entity, colliding_entities)| {
-        if !colliding_entities.0.is_empty() {
-            colliding_entities.0.iter().for_each(|target| {
+        colliding_entities
+            .0
+            .iter()
+            .filter(|target| q_targets.contains(**target))
+            .for_each(|target| {
                 ev_writer.send(ProjectileHitEvent::new(entity, *target));
             });
-        }
     });
 }
@@ -4,10 +4,11 @@ use bevy_xpbd_2d::{
     prelude::*,
 };
 use leafwing_input_manager::prelude::*;
+use serde::{Deserialize, Serialize};
 
 pub struct CharacterControllerPlugin;
 
-use crate::prelude::Player;
+use crate::{bindings::KeyBindings, game_state::GameState, prelude::Player};
 
 impl Plugin for CharacterControllerPlugin {
     fn build(&self, app: &mut App) {
@@ -16,6 +17,10 @@ impl Plugin for CharacterControllerPlugin {
             .add_event::<PlayerMoveEvent>()
             .add_systems(
                 Update,
+                buffer_dash_input.run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                FixedUpdate,
                 (
                     movement_input,
                     movement,
@@ -23,7 +28,8 @@ impl Plugin for CharacterControllerPlugin {
                     update_player_position,
                     tick_dash_cooldown,
                 )
-                    .chain(),
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
             );
     }
 }
@@ -40,20 +46,28 @@ fn update_player_position(
     }
 }
 
-#[derive(Actionlike, PartialEq, Eq, Hash, Clone, Copy, Debug, Reflect)]
+#[derive(Actionlike, PartialEq, Eq, Hash, Clone, Copy, Debug, Reflect, Serialize, Deserialize)]
 pub enum Action {
     Move,
     Dash,
     Shoot,
+    /// Twin-stick aim direction. The mouse aims through [`CursorPosition`](crate::bullet::CursorPosition) instead.
+    Aim,
 }
 
 impl Action {
+    /// Default keyboard and mouse bindings together with [`Action::default_gamepad_map`].
     pub fn default_input_map() -> InputMap<Self> {
+        KeyBindings::default().input_map()
+    }
+
+    pub fn default_gamepad_map() -> InputMap<Self> {
         let mut input_map = InputMap::default();
 
-        input_map.insert(Action::Move, VirtualDPad::wasd());
-        input_map.insert(Action::Dash, KeyCode::ShiftLeft);
-        input_map.insert(Action::Shoot, MouseButton::Left);
+        input_map.insert(Action::Move, DualAxis::left_stick());
+        input_map.insert(Action::Aim, DualAxis::right_stick());
+        input_map.insert(Action::Dash, GamepadButtonType::LeftTrigger);
+        input_map.insert(Action::Shoot, GamepadButtonType::RightTrigger2);
 
         input_map
     }
@@ -71,6 +85,12 @@ pub struct CharacterController;
 #[derive(Debug, Component, Clone, Copy)]
 pub struct MovementAcceleration(pub Scalar);
 
+/// Acceleration before status effects. Config reloads and upgrades change this one, the
+/// [`MovementAcceleration`] follows it.
+#[derive(Debug, Component, Clone, Copy)]
+pub struct BaseAcceleration(pub Scalar);
+
+/// How quickly velocity decays, per second. Velocity is scaled by `exp(-rate * delta)` every tick.
 #[derive(Debug, Component)]
 pub struct MovementDampingFactor(pub Scalar);
 
@@ -85,6 +105,7 @@ pub struct CharacterControllerBundle {
 
 #[derive(Bundle, Debug)]
 pub struct MovementBundle {
+    base_acceleration: BaseAcceleration,
     acceleration: MovementAcceleration,
     damping: MovementDampingFactor,
 }
@@ -92,6 +113,7 @@ pub struct MovementBundle {
 impl MovementBundle {
     pub const fn new(acceleration: Scalar, damping: Scalar) -> Self {
         Self {
+            base_acceleration: BaseAcceleration(acceleration),
             acceleration: MovementAcceleration(acceleration),
             damping: MovementDampingFactor(damping),
         }
@@ -100,7 +122,7 @@ impl MovementBundle {
 
 impl Default for MovementBundle {
     fn default() -> Self {
-        Self::new(1250.0, 0.9)
+        Self::new(1250.0, 6.32)
     }
 }
 
@@ -115,7 +137,6 @@ impl CharacterControllerBundle {
         }
     }
 
-    #[allow(dead_code)]
     pub fn with_movement(mut self, acceleration: Scalar, damping: Scalar) -> Self {
         {
             self.movement = MovementBundle::new(acceleration, damping);
@@ -124,21 +145,48 @@ impl CharacterControllerBundle {
     }
 }
 
+/// A dash press waiting for the next fixed tick, so it isn't lost on frames without one.
+#[derive(Component)]
+struct DashRequested;
+
+fn buffer_dash_input(
+    query: Query<(&ActionState<Action>, Entity), With<Player>>,
+    mut commands: Commands,
+) {
+    if let Ok((action_state, entity)) = query.get_single() {
+        if action_state.just_pressed(&Action::Dash) {
+            commands.entity(entity).insert(DashRequested);
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
 fn movement_input(
-    query: Query<(&ActionState<Action>, Option<&DashCooldown>, Entity), With<Player>>,
+    query: Query<
+        (
+            &ActionState<Action>,
+            Option<&DashCooldown>,
+            Option<&DashRequested>,
+            Entity,
+        ),
+        With<Player>,
+    >,
     mut commands: Commands,
     mut ev_writer: EventWriter<PlayerMoveEvent>,
 ) {
-    if let Ok((action_state, dash_cooldown, entity)) = query.get_single() {
+    if let Ok((action_state, dash_cooldown, dash_requested, entity)) = query.get_single() {
         if action_state.pressed(&Action::Move) {
             let dir = action_state.clamped_axis_pair(&Action::Move).unwrap().xy();
             ev_writer.send(PlayerMoveEvent::Move(dir));
 
-            if action_state.just_pressed(&Action::Dash) && dash_cooldown.is_none() {
+            if dash_requested.is_some() && dash_cooldown.is_none() {
                 ev_writer.send(PlayerMoveEvent::Dash(dir));
                 commands.entity(entity).insert(DashCooldown::default());
             }
         }
+        if dash_requested.is_some() {
+            commands.entity(entity).remove::<DashRequested>();
+        }
     }
 }
 
@@ -185,9 +233,14 @@ fn movement(
     })
 }
 
-fn apply_movement_damping(mut query: Query<(&MovementDampingFactor, &mut LinearVelocity)>) {
+fn apply_movement_damping(
+    time: Res<Time>,
+    mut query: Query<(&MovementDampingFactor, &mut LinearVelocity)>,
+) {
+    let delta_time = time.delta_seconds_f64().adjust_precision();
     query.iter_mut().for_each(|(damping, mut velocity)| {
-        velocity.x *= damping.0;
-        velocity.y *= damping.0;
+        let retained = (-damping.0 * delta_time).exp();
+        velocity.x *= retained;
+        velocity.y *= retained;
     })
 }
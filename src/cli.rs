@@ -0,0 +1,8 @@
+/// Value following `name` on the command line, e.g. `--seed 42`.
+pub fn arg_value(name: &str) -> Option<String> {
+    let args = std::env::args().collect::<Vec<_>>();
+    args.iter()
+        .position(|arg| arg == name)
+        .and_then(|idx| args.get(idx + 1))
+        .cloned()
+}
@@ -0,0 +1,439 @@
+use std::{fmt, marker::PhantomData, path::Path};
+
+use bevy::{
+    asset::{io::Reader, AssetLoadFailedEvent, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    utils::BoxedFuture,
+};
+use serde::Deserialize;
+
+use crate::game_state::{despawn_screen, screen_root, screen_text, GameState};
+
+/// Config resources with their built-in defaults. Enough for simulating without any files.
+pub struct ConfigPlugin;
+
+impl Plugin for ConfigPlugin {
+    fn build(&self, app: &mut App) {
+        register_config::<PlayerConfig>(app);
+        register_config::<EnemyConfig>(app);
+        register_config::<CrumbConfig>(app);
+    }
+}
+
+/// Loads the configs from RON files in `assets`, holding the game in [`GameState::Loading`] until
+/// they are ready, and keeps them in sync with the files.
+pub struct ConfigFilesPlugin;
+
+impl Plugin for ConfigFilesPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ConfigErrors::default())
+            .add_systems(OnEnter(GameState::Loading), spawn_loading_screen)
+            .add_systems(OnExit(GameState::Loading), despawn_screen::<LoadingScreen>)
+            .add_systems(
+                Update,
+                (
+                    finish_loading.run_if(in_state(GameState::Loading)),
+                    update_loading_screen
+                        .run_if(in_state(GameState::Loading))
+                        .run_if(resource_changed::<ConfigErrors>),
+                ),
+            );
+
+        load_config_file::<PlayerConfig>(app);
+        load_config_file::<EnemyConfig>(app);
+        load_config_file::<CrumbConfig>(app);
+    }
+}
+
+/// A balance config that is loaded from a RON file and mirrored into a resource of the same type.
+pub trait ConfigAsset:
+    Asset + Resource + Clone + PartialEq + Default + for<'de> Deserialize<'de>
+{
+    /// Path of the file, relative to the `assets` folder.
+    const PATH: &'static str;
+
+    /// Checks values that parse fine but would break the game, such as a zero health.
+    fn validate(&self) -> Result<(), ConfigLoadError>;
+
+    /// Parses and validates the contents of a config file.
+    fn parse(bytes: &[u8]) -> Result<Self, ConfigLoadError> {
+        let config = ron::de::from_bytes::<Self>(bytes)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Reads the file directly, for tools that run without an [`AssetServer`].
+    fn read_file(assets_dir: &Path) -> Result<Self, ConfigLoadError> {
+        Self::parse(&std::fs::read(assets_dir.join(Self::PATH))?)
+    }
+}
+
+/// Collects the values of a config that are out of range.
+#[derive(Default)]
+struct ConfigProblems(Vec<String>);
+
+impl ConfigProblems {
+    fn positive(&mut self, name: &str, value: f32) -> &mut Self {
+        if value.is_nan() || value <= 0. {
+            self.0
+                .push(format!("{name} must be greater than 0, got {value}"));
+        }
+        self
+    }
+
+    fn non_negative(&mut self, name: &str, value: f32) -> &mut Self {
+        if value.is_nan() || value < 0. {
+            self.0
+                .push(format!("{name} must not be negative, got {value}"));
+        }
+        self
+    }
+
+    fn chance(&mut self, name: &str, value: f32) -> &mut Self {
+        if !(0. ..=1.).contains(&value) {
+            self.0
+                .push(format!("{name} must be within 0..=1, got {value}"));
+        }
+        self
+    }
+
+    fn movement(&mut self, movement: &MovementConfig) -> &mut Self {
+        self.positive("movement.acceleration", movement.acceleration)
+            .non_negative("movement.damping", movement.damping)
+    }
+
+    fn finish(&mut self) -> Result<(), ConfigLoadError> {
+        match self.0.is_empty() {
+            true => Ok(()),
+            false => Err(ConfigLoadError::Invalid(std::mem::take(&mut self.0))),
+        }
+    }
+}
+
+/// Systems that copy freshly (re)loaded configs into their resources.
+/// Systems reacting to [`ConfigReloaded`] should run after this set.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ConfigSyncSet;
+
+/// Sent when a config file is edited on disk and its resource has been replaced.
+#[derive(Event)]
+pub struct ConfigReloaded<T: ConfigAsset>(PhantomData<T>);
+
+impl<T: ConfigAsset> Default for ConfigReloaded<T> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+fn register_config<T: ConfigAsset>(app: &mut App) {
+    app.init_resource::<T>().add_event::<ConfigReloaded<T>>();
+}
+
+fn load_config_file<T: ConfigAsset>(app: &mut App) {
+    app.init_asset::<T>()
+        .register_asset_loader(RonConfigLoader::<T>::default())
+        .add_systems(Startup, load_config::<T>)
+        .add_systems(
+            Update,
+            (
+                sync_config::<T>
+                    .in_set(ConfigSyncSet)
+                    .run_if(on_event::<AssetEvent<T>>()),
+                report_config_errors::<T>.run_if(on_event::<AssetLoadFailedEvent<T>>()),
+            ),
+        );
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct MovementConfig {
+    pub acceleration: f32,
+    /// Velocity decay rate per second, see [`MovementDampingFactor`](crate::character::MovementDampingFactor).
+    pub damping: f32,
+}
+
+#[derive(Asset, Resource, TypePath, Debug, Clone, PartialEq, Deserialize)]
+pub struct PlayerConfig {
+    pub size: f32,
+    pub health: f32,
+    pub collection_radius: f32,
+    pub movement: MovementConfig,
+}
+
+impl Default for PlayerConfig {
+    fn default() -> Self {
+        Self {
+            size: 32.,
+            health: 15.,
+            collection_radius: 200.,
+            movement: MovementConfig {
+                acceleration: 1250.,
+                damping: 6.32,
+            },
+        }
+    }
+}
+
+impl ConfigAsset for PlayerConfig {
+    const PATH: &'static str = "config/player.ron";
+
+    fn validate(&self) -> Result<(), ConfigLoadError> {
+        ConfigProblems::default()
+            .positive("size", self.size)
+            .positive("health", self.health)
+            .positive("collection_radius", self.collection_radius)
+            .movement(&self.movement)
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct RangedConfig {
+    pub keep_distance: f32,
+    pub attack_range: f32,
+}
+
+#[derive(Asset, Resource, TypePath, Debug, Clone, PartialEq, Deserialize)]
+pub struct EnemyConfig {
+    pub size: f32,
+    pub health: f32,
+    pub movement: MovementConfig,
+    pub contact_damage: f32,
+    pub contact_knockback: f32,
+    pub ranged: RangedConfig,
+}
+
+impl Default for EnemyConfig {
+    fn default() -> Self {
+        Self {
+            size: 32.,
+            health: 15.,
+            movement: MovementConfig {
+                acceleration: 700.,
+                damping: 6.32,
+            },
+            contact_damage: 5.,
+            contact_knockback: 170.,
+            ranged: RangedConfig {
+                keep_distance: 250.,
+                attack_range: 400.,
+            },
+        }
+    }
+}
+
+impl ConfigAsset for EnemyConfig {
+    const PATH: &'static str = "config/enemy.ron";
+
+    fn validate(&self) -> Result<(), ConfigLoadError> {
+        ConfigProblems::default()
+            .positive("size", self.size)
+            .positive("health", self.health)
+            .movement(&self.movement)
+            .non_negative("contact_damage", self.contact_damage)
+            .non_negative("contact_knockback", self.contact_knockback)
+            .non_negative("ranged.keep_distance", self.ranged.keep_distance)
+            .positive("ranged.attack_range", self.ranged.attack_range)
+            .finish()
+    }
+}
+
+#[derive(Asset, Resource, TypePath, Debug, Clone, PartialEq, Deserialize)]
+pub struct CrumbConfig {
+    pub xp_value: f32,
+    pub heal_amount: f32,
+    /// Chance in `0..=1` that a dead enemy drops a healing crumb instead of XP.
+    pub healing_drop_chance: f32,
+    /// Chance in `0..=1` that a dead enemy drops a magnet, which pulls in every crumb on the map.
+    pub magnet_drop_chance: f32,
+}
+
+impl Default for CrumbConfig {
+    fn default() -> Self {
+        Self {
+            xp_value: 5.,
+            heal_amount: 5.,
+            healing_drop_chance: 0.09,
+            magnet_drop_chance: 0.01,
+        }
+    }
+}
+
+impl ConfigAsset for CrumbConfig {
+    const PATH: &'static str = "config/crumbs.ron";
+
+    fn validate(&self) -> Result<(), ConfigLoadError> {
+        ConfigProblems::default()
+            .non_negative("xp_value", self.xp_value)
+            .non_negative("heal_amount", self.heal_amount)
+            .chance("healing_drop_chance", self.healing_drop_chance)
+            .chance("magnet_drop_chance", self.magnet_drop_chance)
+            .chance(
+                "healing_drop_chance + magnet_drop_chance",
+                self.healing_drop_chance + self.magnet_drop_chance,
+            )
+            .finish()
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigLoadError {
+    Io(std::io::Error),
+    Parse(ron::error::SpannedError),
+    /// Every value that is out of range.
+    Invalid(Vec<String>),
+}
+
+impl fmt::Display for ConfigLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigLoadError::Io(err) => write!(f, "could not read config: {err}"),
+            ConfigLoadError::Parse(err) => write!(f, "could not parse config: {err}"),
+            ConfigLoadError::Invalid(problems) => {
+                write!(f, "invalid config: {}", problems.join(", "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigLoadError {}
+
+impl From<std::io::Error> for ConfigLoadError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<ron::error::SpannedError> for ConfigLoadError {
+    fn from(err: ron::error::SpannedError) -> Self {
+        Self::Parse(err)
+    }
+}
+
+pub struct RonConfigLoader<T>(PhantomData<T>);
+
+impl<T> Default for RonConfigLoader<T> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T: ConfigAsset> AssetLoader for RonConfigLoader<T> {
+    type Asset = T;
+    type Settings = ();
+    type Error = ConfigLoadError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<T, ConfigLoadError>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            T::parse(&bytes)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["ron"]
+    }
+}
+
+#[derive(Resource)]
+pub struct ConfigHandle<T: ConfigAsset>(pub Handle<T>);
+
+/// Human readable errors of configs that failed to load.
+#[derive(Resource, Default)]
+pub struct ConfigErrors(pub Vec<String>);
+
+fn load_config<T: ConfigAsset>(asset_server: Res<AssetServer>, mut commands: Commands) {
+    commands.insert_resource(ConfigHandle::<T>(asset_server.load(T::PATH)));
+}
+
+fn sync_config<T: ConfigAsset>(
+    mut ev_reader: EventReader<AssetEvent<T>>,
+    handle: Res<ConfigHandle<T>>,
+    assets: Res<Assets<T>>,
+    mut config: ResMut<T>,
+    mut ev_writer: EventWriter<ConfigReloaded<T>>,
+    mut loaded_once: Local<bool>,
+) {
+    ev_reader.read().for_each(|ev| match ev {
+        AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id }
+            if *id == handle.0.id() =>
+        {
+            let Some(loaded) = assets.get(*id) else {
+                return;
+            };
+            let first_load = !std::mem::replace(&mut *loaded_once, true);
+            // A reload may send both events, only the first one actually changes anything
+            if *loaded == *config {
+                return;
+            }
+
+            *config = loaded.clone();
+            if !first_load {
+                info!("Reloaded {}", T::PATH);
+                ev_writer.send(ConfigReloaded::default());
+            }
+        }
+        _ => {}
+    });
+}
+
+fn report_config_errors<T: ConfigAsset>(
+    mut ev_reader: EventReader<AssetLoadFailedEvent<T>>,
+    mut errors: ResMut<ConfigErrors>,
+) {
+    ev_reader.read().for_each(|ev| {
+        let message = format!("Failed to load {}: {}", ev.path, ev.error);
+        error!("{message}");
+        errors.0.push(message);
+    });
+}
+
+fn finish_loading(
+    asset_server: Res<AssetServer>,
+    player: Res<ConfigHandle<PlayerConfig>>,
+    enemy: Res<ConfigHandle<EnemyConfig>>,
+    crumbs: Res<ConfigHandle<CrumbConfig>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let loaded = [
+        player.0.id().untyped(),
+        enemy.0.id().untyped(),
+        crumbs.0.id().untyped(),
+    ]
+    .into_iter()
+    .all(|id| asset_server.is_loaded_with_dependencies(id));
+
+    if loaded {
+        next_state.set(GameState::MainMenu);
+    }
+}
+
+#[derive(Component)]
+struct LoadingScreen;
+
+#[derive(Component)]
+struct LoadingErrorText;
+
+fn spawn_loading_screen(mut commands: Commands) {
+    commands
+        .spawn((screen_root(), LoadingScreen))
+        .with_children(|children| {
+            children.spawn(screen_text("Loading...", 48.));
+            children.spawn((screen_text("", 18.), LoadingErrorText));
+        });
+}
+
+fn update_loading_screen(
+    errors: Res<ConfigErrors>,
+    mut q_text: Query<&mut Text, With<LoadingErrorText>>,
+) {
+    q_text.iter_mut().for_each(|mut text| {
+        text.sections[0].value = errors.0.join("\n");
+        text.sections[0].style.color = Color::RED;
+    });
+}
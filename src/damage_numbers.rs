@@ -0,0 +1,166 @@
+use std::fmt::Debug;
+
+use bevy::{prelude::*, utils::HashSet};
+
+use crate::prelude::*;
+
+const NUMBER_LIFETIME: f32 = 0.8;
+const NUMBER_RISE_SPEED: f32 = 40.;
+const HIT_FLASH_DURATION: f32 = 0.1;
+
+/// Floating numbers for damage and heals, and a white flash on sprites that get hit.
+pub struct DamageNumbersPlugin;
+
+impl Plugin for DamageNumbersPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                (
+                    spawn_damage_numbers::<Enemy>,
+                    spawn_damage_numbers::<Player>,
+                    spawn_heal_numbers::<Player>,
+                    start_hit_flash::<Enemy>,
+                    start_hit_flash::<Player>,
+                ),
+                (float_numbers, end_hit_flash),
+            )
+                .chain(),
+        );
+    }
+}
+
+#[derive(Component)]
+struct FloatingNumber(Timer);
+
+/// Color the sprite had before it started flashing.
+#[derive(Component)]
+struct HitFlash {
+    timer: Timer,
+    color: Color,
+}
+
+fn format_amount(amount: f32) -> String {
+    match amount.fract() == 0. {
+        true => format!("{amount:.0}"),
+        false => format!("{amount:.1}"),
+    }
+}
+
+fn spawn_number(commands: &mut Commands, position: Vec2, text: String, color: Color, size: f32) {
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(
+                text,
+                TextStyle {
+                    font_size: size,
+                    color,
+                    ..Default::default()
+                },
+            ),
+            transform: Transform::from_translation(position.extend(10.) + Vec3::Y * 24.),
+            ..Default::default()
+        },
+        FloatingNumber(Timer::from_seconds(NUMBER_LIFETIME, TimerMode::Once)),
+    ));
+}
+
+fn spawn_damage_numbers<T: Component + Debug>(
+    mut ev_reader: EventReader<EntityEvent<TookDamage, T>>,
+    q_players: Query<(), With<Player>>,
+    mut commands: Commands,
+) {
+    ev_reader
+        .read()
+        .for_each(|EntityEvent { entity, data, .. }| {
+            let (color, size) = match (q_players.contains(*entity), data.critical) {
+                (true, _) => (Color::RED, 24.),
+                (false, true) => (Color::YELLOW, 30.),
+                (false, false) => (Color::WHITE, 20.),
+            };
+            let text = match data.critical {
+                true => format!("{}!", format_amount(data.amount)),
+                false => format_amount(data.amount),
+            };
+            spawn_number(&mut commands, data.position, text, color, size);
+        });
+}
+
+fn spawn_heal_numbers<T: Component + Debug>(
+    mut ev_reader: EventReader<EntityEvent<Healed, T>>,
+    mut commands: Commands,
+) {
+    ev_reader
+        .read()
+        .filter(|EntityEvent { data, .. }| data.amount > 0.)
+        .for_each(|EntityEvent { data, .. }| {
+            let text = format!("+{}", format_amount(data.amount));
+            spawn_number(&mut commands, data.position, text, Color::GREEN, 22.);
+        });
+}
+
+fn float_numbers(
+    time: Res<Time>,
+    mut q_numbers: Query<(Entity, &mut FloatingNumber, &mut Transform, &mut Text)>,
+    mut commands: Commands,
+) {
+    q_numbers
+        .iter_mut()
+        .for_each(|(entity, mut number, mut transform, mut text)| {
+            if number.0.tick(time.delta()).finished() {
+                commands.entity(entity).despawn_recursive();
+                return;
+            }
+
+            transform.translation.y += NUMBER_RISE_SPEED * time.delta_seconds();
+            let opacity = 1. - number.0.fraction();
+            text.sections.iter_mut().for_each(|section| {
+                section.style.color.set_a(opacity);
+            });
+        });
+}
+
+fn start_hit_flash<T: Component + Debug>(
+    mut ev_reader: EventReader<EntityEvent<TookDamage, T>>,
+    mut q_sprites: Query<(&mut Sprite, Option<&mut HitFlash>)>,
+    mut commands: Commands,
+) {
+    // Several hits in one frame must not record the white as the original color
+    let hit = ev_reader
+        .read()
+        .map(|EntityEvent { entity, .. }| *entity)
+        .collect::<HashSet<_>>();
+
+    hit.into_iter().for_each(|entity| {
+        let Ok((mut sprite, hit_flash)) = q_sprites.get_mut(entity) else {
+            return;
+        };
+
+        match hit_flash {
+            Some(mut hit_flash) => hit_flash.timer.reset(),
+            None => {
+                commands.entity(entity).insert(HitFlash {
+                    timer: Timer::from_seconds(HIT_FLASH_DURATION, TimerMode::Once),
+                    color: sprite.color,
+                });
+                // Keep the alpha, blinking entities toggle it on their own
+                sprite.color = Color::WHITE.with_a(sprite.color.a());
+            }
+        }
+    });
+}
+
+fn end_hit_flash(
+    time: Res<Time>,
+    mut q_flashing: Query<(Entity, &mut HitFlash, &mut Sprite)>,
+    mut commands: Commands,
+) {
+    q_flashing
+        .iter_mut()
+        .for_each(|(entity, mut hit_flash, mut sprite)| {
+            if hit_flash.timer.tick(time.delta()).finished() {
+                sprite.color = hit_flash.color.with_a(sprite.color.a());
+                commands.entity(entity).remove::<HitFlash>();
+            }
+        });
+}
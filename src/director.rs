@@ -0,0 +1,259 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    enemy::{EnemyKind, SpawnEnemy},
+    game_state::{GameState, RunTime, RunTimeSet},
+    prelude::*,
+    rng::{GameRng, RngStream},
+};
+
+pub struct DirectorPlugin;
+
+impl Plugin for DirectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SpawnDirector::default()).add_systems(
+            FixedUpdate,
+            run_director
+                .after(RunTimeSet)
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+/// Where the enemies of a single burst appear relative to the player.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Formation {
+    /// Evenly spaced on a circle of `radius` around the player.
+    Ring { radius: f32 },
+    /// Bunched up within `spread` of a random point `distance` away from the player.
+    Cluster { distance: f32, spread: f32 },
+    /// A wall of `length` facing the player from a random direction, `distance` away.
+    Line { distance: f32, length: f32 },
+}
+
+impl Formation {
+    pub fn positions(&self, center: Vec2, count: u32, rng: &mut impl Rng) -> Vec<Vec2> {
+        let angle = rng.gen_range((0.)..(std::f32::consts::TAU));
+        let direction = Vec2::from_angle(angle);
+
+        match *self {
+            Formation::Ring { radius } => (0..count)
+                .map(|idx| {
+                    let offset = std::f32::consts::TAU * idx as f32 / count as f32;
+                    center + Vec2::from_angle(angle + offset) * radius
+                })
+                .collect(),
+            Formation::Cluster { distance, spread } => {
+                let cluster_center = center + direction * distance;
+                (0..count)
+                    .map(|_| {
+                        let offset = Vec2::from_angle(rng.gen_range((0.)..(std::f32::consts::TAU)))
+                            * rng.gen_range((0.)..=spread);
+                        cluster_center + offset
+                    })
+                    .collect()
+            }
+            Formation::Line { distance, length } => {
+                let line_center = center + direction * distance;
+                let along = direction.perp();
+                (0..count)
+                    .map(|idx| {
+                        let t = match count {
+                            1 => 0.5,
+                            _ => idx as f32 / (count - 1) as f32,
+                        };
+                        line_center + along * (t - 0.5) * length
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// How the delay between two bursts of a wave changes as the wave progresses.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SpawnRateCurve {
+    /// Always waits the given number of seconds.
+    Constant(f32),
+    /// Interpolates the delay linearly from `from` to `to` seconds.
+    Linear { from: f32, to: f32 },
+    /// Like [`SpawnRateCurve::Linear`], but stays close to `from` for longer before speeding up.
+    EaseIn { from: f32, to: f32 },
+}
+
+impl SpawnRateCurve {
+    /// Delay until the next burst, `progress` being how far along the wave is in `0..=1`.
+    pub fn delay(&self, progress: f32) -> f32 {
+        let progress = progress.clamp(0., 1.);
+        match *self {
+            SpawnRateCurve::Constant(delay) => delay,
+            SpawnRateCurve::Linear { from, to } => from + (to - from) * progress,
+            SpawnRateCurve::EaseIn { from, to } => from + (to - from) * progress * progress,
+        }
+    }
+}
+
+/// A scripted stretch of the run that spawns bursts of one enemy type.
+#[derive(Debug, Clone)]
+pub struct Wave {
+    /// Run time in seconds at which the wave starts.
+    pub start: f32,
+    /// Run time in seconds at which the wave stops spawning, `f32::INFINITY` to last until the
+    /// run ends.
+    pub end: f32,
+    pub enemy: EnemyKind,
+    /// Enemies spawned per burst.
+    pub count: u32,
+    pub formation: Formation,
+    pub rate: SpawnRateCurve,
+}
+
+impl Wave {
+    fn progress(&self, run_time: f32) -> f32 {
+        (run_time - self.start) / (self.end - self.start)
+    }
+}
+
+/// Spawns every enemy of a run from a wave timeline.
+#[derive(Resource)]
+pub struct SpawnDirector {
+    timeline: Vec<Wave>,
+    next_burst: Vec<f32>,
+}
+
+impl SpawnDirector {
+    pub fn new(timeline: Vec<Wave>) -> Self {
+        Self {
+            next_burst: timeline.iter().map(|wave| wave.start).collect(),
+            timeline,
+        }
+    }
+}
+
+impl Default for SpawnDirector {
+    fn default() -> Self {
+        Self::new(vec![
+            // Opening, a few stragglers to warm up with
+            Wave {
+                start: 1.,
+                end: 20.,
+                enemy: EnemyKind::Melee,
+                count: 1,
+                formation: Formation::Ring { radius: 400. },
+                rate: SpawnRateCurve::Linear { from: 2.5, to: 1.5 },
+            },
+            // Steady pressure between the scripted waves
+            Wave {
+                start: 20.,
+                end: 600.,
+                enemy: EnemyKind::Melee,
+                count: 1,
+                formation: Formation::Ring { radius: 400. },
+                rate: SpawnRateCurve::Linear {
+                    from: 1.5,
+                    to: 0.75,
+                },
+            },
+            Wave {
+                start: 20.,
+                end: 90.,
+                enemy: EnemyKind::Melee,
+                count: 4,
+                formation: Formation::Cluster {
+                    distance: 450.,
+                    spread: 60.,
+                },
+                rate: SpawnRateCurve::Linear { from: 12., to: 8. },
+            },
+            Wave {
+                start: 60.,
+                end: 180.,
+                enemy: EnemyKind::Ranged,
+                count: 3,
+                formation: Formation::Line {
+                    distance: 450.,
+                    length: 200.,
+                },
+                rate: SpawnRateCurve::Constant(12.),
+            },
+            Wave {
+                start: 90.,
+                end: 300.,
+                enemy: EnemyKind::Melee,
+                count: 10,
+                formation: Formation::Ring { radius: 450. },
+                rate: SpawnRateCurve::EaseIn { from: 20., to: 8. },
+            },
+            Wave {
+                start: 180.,
+                end: 600.,
+                enemy: EnemyKind::Ranged,
+                count: 6,
+                formation: Formation::Ring { radius: 500. },
+                rate: SpawnRateCurve::EaseIn { from: 18., to: 6. },
+            },
+            Wave {
+                start: 300.,
+                end: 600.,
+                enemy: EnemyKind::Melee,
+                count: 8,
+                formation: Formation::Cluster {
+                    distance: 400.,
+                    spread: 80.,
+                },
+                rate: SpawnRateCurve::Linear { from: 6., to: 3. },
+            },
+            // Endgame, everything at once until the player goes down
+            Wave {
+                start: 600.,
+                end: f32::INFINITY,
+                enemy: EnemyKind::Melee,
+                count: 12,
+                formation: Formation::Ring { radius: 450. },
+                rate: SpawnRateCurve::Constant(3.),
+            },
+            Wave {
+                start: 600.,
+                end: f32::INFINITY,
+                enemy: EnemyKind::Ranged,
+                count: 6,
+                formation: Formation::Ring { radius: 500. },
+                rate: SpawnRateCurve::Constant(5.),
+            },
+        ])
+    }
+}
+
+fn run_director(
+    run_time: Res<RunTime>,
+    player_pos: Res<PlayerPosition>,
+    mut director: ResMut<SpawnDirector>,
+    mut game_rng: ResMut<GameRng>,
+    mut commands: Commands,
+) {
+    let now = run_time.0.elapsed_secs();
+    let SpawnDirector {
+        timeline,
+        next_burst,
+    } = &mut *director;
+
+    timeline
+        .iter()
+        .zip(next_burst.iter_mut())
+        .filter(|(wave, next)| now >= **next && now < wave.end)
+        .for_each(|(wave, next)| {
+            wave.formation
+                .positions(
+                    player_pos.0,
+                    wave.count,
+                    game_rng.stream(RngStream::Director),
+                )
+                .into_iter()
+                .for_each(|position| {
+                    commands.add(SpawnEnemy::new(position, wave.enemy));
+                });
+
+            *next = now + wave.rate.delay(wave.progress(now));
+        });
+}
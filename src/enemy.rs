@@ -1,16 +1,24 @@
-use std::{ops::Div, time::Duration};
+use std::ops::Div;
 
-use bevy::ecs::system::{Command, SystemState};
+use bevy::ecs::system::{Command, EntityCommand};
 use bevy::prelude::*;
 use bevy_xpbd_2d::{math::AdjustPrecision, prelude::*};
 use rand::Rng;
 
-use crate::healthbar::{DeleteHealthbar, SpawnHealthbar};
+use crate::character::MovementDampingFactor;
 use crate::{
-    bullet::{Projectile, ProjectileDamage},
+    bullet::{
+        CritChance, OnHitEffect, Projectile, ProjectileDamage, ProjectileKnockback, ShotDirection,
+    },
+    config::{ConfigReloaded, ConfigSyncSet, CrumbConfig, EnemyConfig},
+    game_state::{GameState, RunTime, RunTimeSet},
     hurtbox::TakeDamage,
+    knockback::{Knockback, KnockbackMass, Stunned, PROJECTILE_STUN},
     prelude::*,
-    xp_crumbs::{HealingCrumb, SpawnCrumb, XpCrumb},
+    rng::{GameRng, RngStream},
+    status_effects::ApplyStatusEffect,
+    weapon::{EquipWeapon, Weapon, CRIT_MULTIPLIER},
+    xp_crumbs::{HealingCrumb, MagnetCrumb, SpawnCrumb, XpCrumb},
 };
 
 pub struct EnemyPlugin;
@@ -21,87 +29,63 @@ impl Plugin for EnemyPlugin {
             .add_event::<EnemyTouchedPlayerEvent>()
             .add_event::<EntityEvent<TookDamage, Enemy>>()
             .add_event::<EntityEvent<Died, Enemy>>()
-            .insert_resource(EnemySpawner::default())
             .add_systems(
-                Update,
+                FixedUpdate,
                 (
                     (handle_projectile_hits).run_if(on_event::<ProjectileHitEvent<Enemy>>()),
                     (enemy_on_dead_system).run_if(on_event::<EntityEvent<Died, Enemy>>()),
-                    spawn_enemies,
                     update_enemy_health_scaling,
-                    update_spawner_timer,
                     emit_player_contact_events,
                     move_enemies_system,
                 )
-                    .chain(),
+                    .chain()
+                    .after(RunTimeSet)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                Update,
+                apply_enemy_config
+                    .after(ConfigSyncSet)
+                    .run_if(on_event::<ConfigReloaded<EnemyConfig>>()),
             );
     }
 }
 
 #[derive(Resource)]
-struct EnemySpawner {
-    min_delay: Duration,
-    max_delay: Duration,
-    timer: Timer,
-}
+pub(crate) struct EnemyHealthScaling(pub(crate) f32);
 
-impl Default for EnemySpawner {
-    fn default() -> Self {
-        Self::new(1., 2.)
-    }
-}
+/// The [`EnemyHealthScaling`] the enemy was spawned with, kept so a reload can rescale it.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct HealthScale(pub f32);
 
-#[derive(Resource)]
-struct EnemyHealthScaling(f32);
+fn update_enemy_health_scaling(run_time: Res<RunTime>, mut scaling: ResMut<EnemyHealthScaling>) {
+    scaling.0 = 1_f32.max(run_time.0.elapsed_secs().div_euclid(60.).div(2.));
+}
 
-fn update_enemy_health_scaling(time: Res<Time>, mut scaling: ResMut<EnemyHealthScaling>) {
-    scaling.0 = (1. as f32).max(time.elapsed_seconds().div_euclid(60.).div(2.));
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnemyKind {
+    /// Runs straight at the player and damages on contact.
+    Melee,
+    /// Keeps its distance and shoots at the player.
+    Ranged,
 }
 
-impl EnemySpawner {
-    fn get_timer(min_delay: f32, max_delay: f32) -> Timer {
-        Timer::from_seconds(
-            rand::thread_rng().gen_range(min_delay..max_delay),
-            TimerMode::Once,
-        )
-    }
+/// Distance from the player that an enemy tries to hover at instead of closing in.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct KeepDistance(pub f32);
 
-    fn new(min_delay: f32, max_delay: f32) -> Self {
-        Self {
-            min_delay: Duration::from_secs_f32(min_delay),
-            max_delay: Duration::from_secs_f32(max_delay),
-            timer: Self::get_timer(min_delay, max_delay),
-        }
-    }
-
-    fn update(&mut self) {
-        self.timer = Self::get_timer(self.min_delay.as_secs_f32(), self.max_delay.as_secs_f32());
-    }
-}
+/// Maximum distance to the player at which an enemy will fire its weapons.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct AttackRange(pub f32);
 
-struct SpawnEnemy {
+pub struct SpawnEnemy {
     position: Vec2,
+    kind: EnemyKind,
 }
 
 impl SpawnEnemy {
-    fn angle_from_player(
-        player_position: Vec2,
-        distance_from_player: f32,
-        attack_angle: f32,
-    ) -> Self {
-        let new_spawn_vec = Vec2::X * distance_from_player;
-        let result_position = Quat::from_rotation_z(attack_angle)
-            .mul_vec3(new_spawn_vec.extend(0.))
-            .truncate();
-
-        Self {
-            position: result_position + player_position,
-        }
-    }
-
-    fn random_angle(player_position: Vec2, distance_from_player: f32) -> Self {
-        let attack_angle = rand::thread_rng().gen_range((0.)..(std::f32::consts::TAU));
-        Self::angle_from_player(player_position, distance_from_player, attack_angle)
+    pub fn new(position: Vec2, kind: EnemyKind) -> Self {
+        Self { position, kind }
     }
 }
 
@@ -109,42 +93,89 @@ impl Command for SpawnEnemy {
     fn apply(self, world: &mut World) {
         let scaling = world
             .get_resource::<EnemyHealthScaling>()
-            .expect("Failed to obtain enemy health scaling handle");
+            .expect("Failed to obtain enemy health scaling handle")
+            .0;
+        let config = world
+            .get_resource::<EnemyConfig>()
+            .expect("Failed to obtain enemy config")
+            .clone();
 
         let enemy = world
             .spawn((
-                EnemyBundle::new(Collider::circle(16.), 15. * scaling.0),
-                EnemyBundle::sprite_bundle(Transform::from_xyz(
-                    self.position.x,
-                    self.position.y,
-                    0.,
-                )),
+                EnemyBundle::new(Collider::circle(config.size / 2.), config.health * scaling)
+                    .with_movement(config.movement.acceleration, config.movement.damping),
+                EnemyBundle::sprite_bundle(
+                    self.kind,
+                    config.size,
+                    Transform::from_xyz(self.position.x, self.position.y, 0.),
+                ),
                 LockedAxes::ROTATION_LOCKED,
+                KnockbackMass::from_size(config.size),
+                HealthScale(scaling),
             ))
             .id();
 
-        let mut system_state = SystemState::<Commands>::new(world);
-        let mut commands = system_state.get_mut(world);
-        commands.add(SpawnHealthbar::new(enemy));
-        system_state.apply(world);
+        if self.kind == EnemyKind::Ranged {
+            world.entity_mut(enemy).insert((
+                KeepDistance(config.ranged.keep_distance),
+                AttackRange(config.ranged.attack_range),
+            ));
+            EquipWeapon(Weapon::enemy_bolt()).apply(enemy, world);
+        }
     }
 }
 
-fn spawn_enemies(
-    time: Res<Time>,
-    player_pos: Res<PlayerPosition>,
-    mut spawner: ResMut<EnemySpawner>,
-    mut commands: Commands,
+/// Applies an edited enemy config to the enemies that are already alive.
+#[allow(clippy::type_complexity)]
+fn apply_enemy_config(
+    mut ev_reader: EventReader<ConfigReloaded<EnemyConfig>>,
+    config: Res<EnemyConfig>,
+    mut q_enemies: Query<
+        (
+            &mut BaseAcceleration,
+            &mut MovementDampingFactor,
+            &mut Health,
+            &HealthScale,
+            &mut Collider,
+            &mut Sprite,
+            &mut KnockbackMass,
+            Option<&mut KeepDistance>,
+            Option<&mut AttackRange>,
+        ),
+        With<Enemy>,
+    >,
 ) {
-    if spawner.timer.tick(time.delta()).finished() {
-        commands.add(SpawnEnemy::random_angle(player_pos.0, 400.));
-    }
-}
-
-fn update_spawner_timer(mut spawner: ResMut<EnemySpawner>) {
-    if spawner.timer.finished() {
-        spawner.update();
-    }
+    ev_reader.clear();
+
+    q_enemies.iter_mut().for_each(
+        |(
+            mut acceleration,
+            mut damping,
+            mut health,
+            health_scale,
+            mut collider,
+            mut sprite,
+            mut mass,
+            keep_distance,
+            attack_range,
+        )| {
+            acceleration.0 = config.movement.acceleration;
+            damping.0 = config.movement.damping;
+            // Keep the scaling from when the enemy spawned and how hurt it is
+            let remaining = health.remaining_fraction();
+            health.max_hp = config.health * health_scale.0;
+            health.cur_hp = health.max_hp * remaining;
+            *collider = Collider::circle(config.size / 2.);
+            sprite.custom_size = Some(Vec2::splat(config.size));
+            *mass = KnockbackMass::from_size(config.size);
+            if let Some(mut keep_distance) = keep_distance {
+                keep_distance.0 = config.ranged.keep_distance;
+            }
+            if let Some(mut attack_range) = attack_range {
+                attack_range.0 = config.ranged.attack_range;
+            }
+        },
+    );
 }
 
 #[derive(Bundle)]
@@ -163,7 +194,7 @@ impl EnemyBundle {
             enemy: Enemy,
             rigid_body: RigidBody::Dynamic,
             collider,
-            movement: MovementBundle::new(700., 0.9),
+            movement: MovementBundle::default(),
             collision_layers: CollisionLayers::new(
                 GameLayer::Enemy,
                 [GameLayer::Enemy, GameLayer::Player, GameLayer::Bullet],
@@ -172,39 +203,70 @@ impl EnemyBundle {
         }
     }
 
-    pub fn sprite() -> Sprite {
+    pub fn with_movement(mut self, acceleration: f32, damping: f32) -> Self {
+        self.movement = MovementBundle::new(acceleration, damping);
+        self
+    }
+
+    pub fn sprite(kind: EnemyKind, size: f32) -> Sprite {
+        let color = match kind {
+            EnemyKind::Melee => Color::BLUE,
+            EnemyKind::Ranged => Color::PURPLE,
+        };
         Sprite {
-            color: Color::BLUE,
-            custom_size: Some(Vec2::splat(32.)),
+            color,
+            custom_size: Some(Vec2::splat(size)),
             ..Default::default()
         }
     }
 
-    pub fn sprite_bundle(transform: Transform) -> SpriteBundle {
+    pub fn sprite_bundle(kind: EnemyKind, size: f32, transform: Transform) -> SpriteBundle {
         SpriteBundle {
-            sprite: Self::sprite(),
+            sprite: Self::sprite(kind, size),
             transform,
             ..Default::default()
         }
     }
 }
 
+#[allow(clippy::type_complexity)]
 fn handle_projectile_hits(
-    q_projectiles: Query<&ProjectileDamage, With<Projectile>>,
+    q_projectiles: Query<
+        (
+            &ProjectileDamage,
+            &ProjectileKnockback,
+            &ShotDirection,
+            Option<&OnHitEffect>,
+            Option<&CritChance>,
+        ),
+        With<Projectile>,
+    >,
     mut ev_reader: EventReader<ProjectileHitEvent<Enemy>>,
+    mut game_rng: ResMut<GameRng>,
     mut commands: Commands,
 ) {
     ev_reader.read().for_each(
         |ProjectileHitEvent::<Enemy> {
              projectile, target, ..
          }| {
-            let projectile_damage = q_projectiles
+            let (projectile_damage, knockback, direction, on_hit, crit_chance) = q_projectiles
                 .get(*projectile)
                 .expect("Failed to find projectile");
 
+            // Only projectiles that can crit roll, so other weapons don't shift the stream
+            let critical = crit_chance
+                .is_some_and(|chance| game_rng.stream(RngStream::Crits).gen::<f32>() < chance.0);
+            let damage = match critical {
+                true => TakeDamage::<Enemy>::new(projectile_damage.0 * CRIT_MULTIPLIER).critical(),
+                false => TakeDamage::<Enemy>::new(projectile_damage.0),
+            };
             commands
                 .entity(*target)
-                .add(TakeDamage::<Enemy>::new(projectile_damage.0));
+                .add(damage)
+                .add(Knockback::new(direction.0, knockback.0).with_stun(PROJECTILE_STUN));
+            if let Some(on_hit) = on_hit {
+                commands.entity(*target).add(ApplyStatusEffect(on_hit.0));
+            }
             commands.entity(*projectile).add(RemoveEntity);
         },
     );
@@ -213,6 +275,8 @@ fn handle_projectile_hits(
 fn enemy_on_dead_system(
     q_enemies: Query<&Transform, With<Enemy>>,
     mut dead_reader: EventReader<EntityEvent<Died, Enemy>>,
+    crumb_config: Res<CrumbConfig>,
+    mut game_rng: ResMut<GameRng>,
     mut commands: Commands,
 ) {
     dead_reader
@@ -223,20 +287,24 @@ fn enemy_on_dead_system(
                 .expect("Entity not found")
                 .translation;
 
-            match rand::thread_rng().gen_range(0..100) {
-                0..=90 => commands.add(SpawnCrumb::<XpCrumb>::new(enemy_translation.truncate())),
-                _ => commands.add(SpawnCrumb::<HealingCrumb>::new(
-                    enemy_translation.truncate(),
-                )),
+            // A single roll per drop, so the magnet chance doesn't shift the rest of the stream
+            let position = enemy_translation.truncate();
+            match game_rng.stream(RngStream::Drops).gen::<f32>() {
+                roll if roll < crumb_config.healing_drop_chance => {
+                    commands.add(SpawnCrumb::<HealingCrumb>::new(position))
+                }
+                roll if roll
+                    < crumb_config.healing_drop_chance + crumb_config.magnet_drop_chance =>
+                {
+                    commands.add(SpawnCrumb::<MagnetCrumb>::new(position))
+                }
+                _ => commands.add(SpawnCrumb::<XpCrumb>::new(position)),
             };
 
             commands.entity(*entity).add(RemoveEntity);
         });
 }
 
-#[derive(Component)]
-pub struct Invulnerable;
-
 #[derive(Event)]
 pub struct EnemyTouchedPlayerEvent {
     pub enemy: Entity,
@@ -264,17 +332,32 @@ fn emit_player_contact_events(
     }
 }
 
+#[allow(clippy::type_complexity)]
 fn move_enemies_system(
     time: Res<Time>,
     player_pos: Res<PlayerPosition>,
-    mut controllers: Query<(&MovementAcceleration, &Transform, &mut LinearVelocity), With<Enemy>>,
+    mut controllers: Query<
+        (
+            &MovementAcceleration,
+            &Transform,
+            &mut LinearVelocity,
+            Option<&KeepDistance>,
+        ),
+        (With<Enemy>, Without<Stunned>),
+    >,
 ) {
     let delta_time = time.delta_seconds_f64().adjust_precision();
     controllers
         .iter_mut()
-        .for_each(|(acceleration, transform, mut velocity)| {
-            let to_player_vec = (player_pos.0 - transform.translation.truncate()).normalize();
-            velocity.x += to_player_vec.x * acceleration.0 * delta_time;
-            velocity.y += to_player_vec.y * acceleration.0 * delta_time;
+        .for_each(|(acceleration, transform, mut velocity, keep_distance)| {
+            let to_player = player_pos.0 - transform.translation.truncate();
+            let to_player_vec = to_player.normalize_or_zero();
+            let heading = match keep_distance {
+                Some(KeepDistance(distance)) if to_player.length() < distance * 0.8 => -1.,
+                Some(KeepDistance(distance)) if to_player.length() < distance * 1.2 => 0.,
+                _ => 1.,
+            };
+            velocity.x += heading * to_player_vec.x * acceleration.0 * delta_time;
+            velocity.y += heading * to_player_vec.y * acceleration.0 * delta_time;
         })
 }
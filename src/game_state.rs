@@ -0,0 +1,299 @@
+use bevy::{ecs::system::Command, prelude::*, time::Stopwatch};
+use bevy_xpbd_2d::prelude::*;
+
+use crate::{
+    bullet::Projectile,
+    config::PlayerConfig,
+    director::SpawnDirector,
+    enemy::EnemyHealthScaling,
+    prelude::*,
+    replay::ReplayPlayback,
+    rng::{GameRng, RunSeed},
+    stats::RunStats,
+    upgrades::UpgradePool,
+    xp_crumbs::{Crumb, PlayerLevel},
+};
+
+pub struct GameStatePlugin;
+
+impl Plugin for GameStatePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<GameState>()
+            .insert_resource(RunTime::default())
+            .add_systems(OnEnter(GameState::MainMenu), (end_run, spawn_main_menu))
+            .add_systems(
+                OnExit(GameState::MainMenu),
+                despawn_screen::<MainMenuScreen>,
+            )
+            .add_systems(
+                OnTransition {
+                    from: GameState::MainMenu,
+                    to: GameState::Playing,
+                },
+                start_run,
+            )
+            .add_systems(
+                OnTransition {
+                    from: GameState::GameOver,
+                    to: GameState::Playing,
+                },
+                start_run,
+            )
+            .add_systems(OnEnter(GameState::Playing), unpause_physics)
+            .add_systems(OnExit(GameState::Playing), pause_physics)
+            .add_systems(OnEnter(GameState::Paused), spawn_pause_screen)
+            .add_systems(OnExit(GameState::Paused), despawn_screen::<PauseScreen>)
+            .add_systems(OnEnter(GameState::GameOver), spawn_game_over_screen)
+            .add_systems(
+                OnExit(GameState::GameOver),
+                despawn_screen::<GameOverScreen>,
+            )
+            .add_systems(
+                Update,
+                (
+                    main_menu_input.run_if(in_state(GameState::MainMenu)),
+                    pause_input
+                        .run_if(in_state(GameState::Playing))
+                        .run_if(not(resource_exists::<ReplayPlayback>)),
+                    paused_input.run_if(in_state(GameState::Paused)),
+                    game_over_input.run_if(in_state(GameState::GameOver)),
+                ),
+            )
+            .add_systems(
+                FixedUpdate,
+                tick_run_time
+                    .in_set(RunTimeSet)
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+#[derive(States, Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum GameState {
+    /// Waiting for the config files to load.
+    #[default]
+    Loading,
+    MainMenu,
+    /// Remapping the key bindings, entered from the main menu.
+    Bindings,
+    /// Toggling the assist options, entered from the main menu.
+    Accessibility,
+    Playing,
+    Paused,
+    LevelUp,
+    GameOver,
+}
+
+/// Time spent in [`GameState::Playing`] during the current run.
+#[derive(Resource, Default)]
+pub struct RunTime(pub Stopwatch);
+
+/// Fixed update systems reading [`RunTime`] should run after this set, so that they see the
+/// same time on every run.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RunTimeSet;
+
+fn tick_run_time(time: Res<Time>, mut run_time: ResMut<RunTime>) {
+    run_time.0.tick(time.delta());
+}
+
+/// Despawns everything left over from the previous run.
+pub struct EndRun;
+
+impl Command for EndRun {
+    fn apply(self, world: &mut World) {
+        let run_entities = world
+            .query_filtered::<Entity, Or<(
+                With<Player>,
+                With<Enemy>,
+                With<Projectile>,
+                With<Crumb>,
+            )>>()
+            .iter(world)
+            .collect::<Vec<_>>();
+
+        run_entities.into_iter().for_each(|entity| {
+            if let Some(entity_mut) = world.get_entity_mut(entity) {
+                entity_mut.despawn_recursive();
+            }
+        });
+    }
+}
+
+/// Tears down the previous run, resets the run resources and spawns a fresh player.
+pub struct StartRun;
+
+impl Command for StartRun {
+    fn apply(self, world: &mut World) {
+        EndRun.apply(world);
+
+        let seed = world.resource::<RunSeed>().0.unwrap_or_else(rand::random);
+        let game_rng = GameRng::new(seed);
+        info!("Starting run with seed {seed}");
+
+        // Leftover time from the menus would shift when the first fixed ticks happen
+        let overstep = world.resource::<Time<Fixed>>().overstep();
+        world
+            .resource_mut::<Time<Fixed>>()
+            .discard_overstep(overstep);
+
+        world.insert_resource(RunTime::default());
+        world.insert_resource(RunStats::default());
+        world.insert_resource(PlayerLevel::default());
+        world.insert_resource(PlayerPosition(Vec2::ZERO));
+        world.insert_resource(game_rng);
+        world.insert_resource(SpawnDirector::default());
+        world.insert_resource(EnemyHealthScaling(1.));
+        world.insert_resource(UpgradePool::default());
+
+        SpawnPlayer::from_config(world.resource::<PlayerConfig>()).apply(world);
+    }
+}
+
+fn start_run(mut commands: Commands) {
+    commands.add(StartRun);
+}
+
+fn end_run(mut commands: Commands) {
+    commands.add(EndRun);
+}
+
+fn pause_physics(mut time: ResMut<Time<Physics>>) {
+    time.pause();
+}
+
+fn unpause_physics(mut time: ResMut<Time<Physics>>) {
+    time.unpause();
+}
+
+fn main_menu_input(keys: Res<ButtonInput<KeyCode>>, mut next_state: ResMut<NextState<GameState>>) {
+    if keys.just_pressed(KeyCode::Enter) {
+        next_state.set(GameState::Playing);
+    } else if keys.just_pressed(KeyCode::KeyB) {
+        next_state.set(GameState::Bindings);
+    } else if keys.just_pressed(KeyCode::KeyA) {
+        next_state.set(GameState::Accessibility);
+    }
+}
+
+fn pause_input(keys: Res<ButtonInput<KeyCode>>, mut next_state: ResMut<NextState<GameState>>) {
+    if keys.just_pressed(KeyCode::Escape) {
+        next_state.set(GameState::Paused);
+    }
+}
+
+fn paused_input(keys: Res<ButtonInput<KeyCode>>, mut next_state: ResMut<NextState<GameState>>) {
+    if keys.just_pressed(KeyCode::Escape) {
+        next_state.set(GameState::Playing);
+    } else if keys.just_pressed(KeyCode::KeyQ) {
+        next_state.set(GameState::MainMenu);
+    }
+}
+
+fn game_over_input(keys: Res<ButtonInput<KeyCode>>, mut next_state: ResMut<NextState<GameState>>) {
+    if keys.just_pressed(KeyCode::KeyR) || keys.just_pressed(KeyCode::Enter) {
+        next_state.set(GameState::Playing);
+    } else if keys.just_pressed(KeyCode::Escape) {
+        next_state.set(GameState::MainMenu);
+    }
+}
+
+#[derive(Component)]
+struct MainMenuScreen;
+
+#[derive(Component)]
+struct PauseScreen;
+
+#[derive(Component)]
+struct GameOverScreen;
+
+pub(crate) fn despawn_screen<T: Component>(
+    q_screen: Query<Entity, With<T>>,
+    mut commands: Commands,
+) {
+    q_screen.iter().for_each(|entity| {
+        commands.entity(entity).despawn_recursive();
+    });
+}
+
+pub(crate) fn screen_root() -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            width: Val::Percent(100.),
+            height: Val::Percent(100.),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            row_gap: Val::Px(16.),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+pub(crate) fn screen_text(text: impl Into<String>, font_size: f32) -> TextBundle {
+    TextBundle::from_section(
+        text,
+        TextStyle {
+            font_size,
+            color: Color::WHITE,
+            ..Default::default()
+        },
+    )
+}
+
+fn spawn_main_menu(mut commands: Commands) {
+    commands
+        .spawn((screen_root(), MainMenuScreen))
+        .with_children(|children| {
+            children.spawn(screen_text("Cubes Attack", 64.));
+            children.spawn(screen_text("Press Enter to start", 24.));
+            children.spawn(screen_text("B - key bindings, A - accessibility", 18.));
+        });
+}
+
+fn spawn_pause_screen(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                background_color: Color::rgba(0., 0., 0., 0.5).into(),
+                ..screen_root()
+            },
+            PauseScreen,
+        ))
+        .with_children(|children| {
+            children.spawn(screen_text("Paused", 48.));
+            children.spawn(screen_text("Esc - resume, Q - quit to menu", 24.));
+        });
+}
+
+fn spawn_game_over_screen(
+    mut commands: Commands,
+    run_time: Res<RunTime>,
+    player_level: Res<PlayerLevel>,
+    game_rng: Res<GameRng>,
+) {
+    let survived = run_time.0.elapsed_secs();
+    commands
+        .spawn((
+            NodeBundle {
+                background_color: Color::rgba(0., 0., 0., 0.7).into(),
+                ..screen_root()
+            },
+            GameOverScreen,
+        ))
+        .with_children(|children| {
+            children.spawn(screen_text("Game Over", 64.));
+            children.spawn(screen_text(
+                format!(
+                    "Survived {:02}:{:02}, reached level {}",
+                    (survived / 60.) as u32,
+                    (survived % 60.) as u32,
+                    player_level.cur_level
+                ),
+                24.,
+            ));
+            children.spawn(screen_text(format!("Seed {}", game_rng.seed()), 18.));
+            children.spawn(screen_text("R - restart, Esc - main menu", 24.));
+        });
+}
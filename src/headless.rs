@@ -0,0 +1,149 @@
+use std::time::Duration;
+
+use bevy::{
+    hierarchy::HierarchyPlugin, input::InputPlugin, prelude::*, time::TimeUpdateStrategy,
+    transform::TransformPlugin,
+};
+use leafwing_input_manager::{axislike::DualAxisData, plugin::InputManagerSystem, prelude::*};
+
+use crate::{
+    bullet::{AimSet, CursorPosition},
+    game_state::GameState,
+    prelude::*,
+    replay::ReplayPlayback,
+    upgrades::{ChooseUpgrade, UpgradeChoices},
+};
+
+/// Runs the simulation without a window or renderer, on top of [`MinimalPlugins`].
+///
+/// Every update advances the game by exactly one fixed tick, menus are skipped and the
+/// player is driven by [`ScriptedInput`] unless a [`ReplayPlayback`] is running.
+pub struct HeadlessPlugin;
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((TransformPlugin, HierarchyPlugin, InputPlugin))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+                1. / 60.,
+            )))
+            .init_resource::<ScriptedInput>()
+            .add_systems(
+                PreUpdate,
+                apply_scripted_input
+                    .in_set(InputManagerSystem::ManualControl)
+                    .run_if(not(resource_exists::<ReplayPlayback>)),
+            )
+            .add_systems(
+                Update,
+                (
+                    skip_loading.run_if(in_state(GameState::Loading)),
+                    skip_main_menu.run_if(in_state(GameState::MainMenu)),
+                    // Aims when the mouse would, so the fixed update sees it a frame later
+                    aim_scripted_input
+                        .in_set(AimSet)
+                        .run_if(in_state(GameState::Playing))
+                        .run_if(not(resource_exists::<ReplayPlayback>)),
+                    pick_scripted_upgrade
+                        .run_if(in_state(GameState::LevelUp))
+                        .run_if(not(resource_exists::<ReplayPlayback>)),
+                ),
+            );
+    }
+}
+
+/// Player input set from code, e.g. by tests or bots, instead of a keyboard and mouse.
+#[derive(Resource, Debug, Default, Clone)]
+pub struct ScriptedInput {
+    /// Movement direction, clamped to unit length like a d-pad.
+    pub movement: Vec2,
+    /// Dashes when this turns from `false` to `true`.
+    pub dash: bool,
+    pub shoot: bool,
+    /// Direction to shoot in, relative to the player.
+    pub aim: Vec2,
+    /// Index of the upgrade picked on level up, falling back to the last offered one.
+    pub upgrade: usize,
+}
+
+fn apply_scripted_input(
+    script: Res<ScriptedInput>,
+    mut q_player: Query<&mut ActionState<Action>, With<Player>>,
+    mut was_dashing: Local<bool>,
+) {
+    let Ok(mut action_state) = q_player.get_single_mut() else {
+        return;
+    };
+    let dash = script.dash && !*was_dashing;
+    *was_dashing = script.dash;
+
+    let mut set_pressed = |action: Action, pressed: bool| match pressed {
+        true => action_state.press(&action),
+        false => action_state.release(&action),
+    };
+    set_pressed(Action::Move, script.movement != Vec2::ZERO);
+    set_pressed(Action::Dash, dash);
+    set_pressed(Action::Shoot, script.shoot);
+
+    action_state
+        .action_data_mut_or_default(&Action::Move)
+        .axis_pair = Some(DualAxisData::from_xy(script.movement));
+}
+
+fn aim_scripted_input(
+    script: Res<ScriptedInput>,
+    player_pos: Res<PlayerPosition>,
+    mut cursor_position: ResMut<CursorPosition>,
+) {
+    cursor_position.0 = player_pos.0 + script.aim;
+}
+
+fn skip_loading(mut next_state: ResMut<NextState<GameState>>) {
+    next_state.set(GameState::MainMenu);
+}
+
+fn skip_main_menu(mut next_state: ResMut<NextState<GameState>>) {
+    next_state.set(GameState::Playing);
+}
+
+fn pick_scripted_upgrade(
+    script: Res<ScriptedInput>,
+    choices: Res<UpgradeChoices>,
+    mut commands: Commands,
+) {
+    let picked = choices
+        .offered
+        .get(script.upgrade)
+        .or(choices.offered.last())
+        .copied();
+
+    if let Some(upgrade) = picked {
+        commands.add(ChooseUpgrade(upgrade));
+    }
+}
+
+/// A simple scripted player: shoots at the closest enemy and backs away when it gets close.
+pub fn kite_nearest_enemy(
+    q_enemies: Query<&Transform, With<Enemy>>,
+    player_pos: Res<PlayerPosition>,
+    mut script: ResMut<ScriptedInput>,
+) {
+    let nearest = q_enemies
+        .iter()
+        .map(|transform| transform.translation.truncate() - player_pos.0)
+        .min_by(|a, b| a.length_squared().total_cmp(&b.length_squared()));
+
+    match nearest {
+        Some(to_enemy) => {
+            script.aim = to_enemy;
+            script.shoot = true;
+            script.movement = match to_enemy.length() < 200. {
+                true => -to_enemy.normalize_or_zero(),
+                false => Vec2::ZERO,
+            };
+        }
+        None => {
+            script.shoot = false;
+            script.movement = Vec2::ZERO;
+        }
+    }
+}
@@ -9,7 +9,10 @@ pub struct HealthbarPlugin;
 
 impl Plugin for HealthbarPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, (update_healthbars));
+        app.init_resource::<HealthbarAssets>().add_systems(
+            Update,
+            (attach_healthbars, update_healthbars, remove_healthbars),
+        );
     }
 }
 
@@ -23,89 +26,95 @@ pub struct HealthbarBackground;
 #[derive(Component)]
 pub struct HealthbarForeground;
 
-#[derive(Component)]
-pub struct BarOffset(Vec2);
-
 impl SpawnHealthbar {
     pub fn new(tracked_entity: Entity) -> Self {
         Self { tracked_entity }
     }
 }
 
-#[derive(Component)]
-pub enum HealthbarType {
-    Static,
-    Moving,
-}
-
 impl Command for SpawnHealthbar {
     fn apply(self, world: &mut World) {
-        let mut system_state = SystemState::<Query<(&Health, &Transform)>>::new(world);
+        let mut system_state = SystemState::<Query<&Health>>::new(world);
         let query = system_state.get(world);
-        let (hp_ref, tr_ref) = query
+        let entity_health = *query
             .get(self.tracked_entity)
             .expect("Entity does not exist");
-        let (entity_health, entity_tr) = (*hp_ref, *tr_ref);
 
-        let max_width = 32.;
-        let remaining_width = max_width * entity_health.remaining_fraction();
-        let bar_height = 10.;
+        let assets = world.resource::<HealthbarAssets>().clone();
 
-        let bg_mesh = world.resource_scope(|_world, mut meshes: Mut<Assets<Mesh>>| {
-            meshes.add(Mesh::from(Rectangle::new(max_width, bar_height)))
-        });
-
-        let bg_material =
-            world.resource_scope(|_world, mut materials: Mut<Assets<ColorMaterial>>| {
-                materials.add(ColorMaterial::from(Color::RED))
-            });
-
-        let fg_mesh = world.resource_scope(|_world, mut meshes: Mut<Assets<Mesh>>| {
-            meshes.add(Mesh::from(Rectangle::new(remaining_width, bar_height)))
-        });
-
-        let fg_material =
-            world.resource_scope(|_world, mut materials: Mut<Assets<ColorMaterial>>| {
-                materials.add(ColorMaterial::from(Color::GREEN))
-            });
-
-        let bar = world
+        let mut foreground = Entity::PLACEHOLDER;
+        let background = world
             .spawn((
                 MaterialMesh2dBundle {
-                    mesh: bg_mesh.into(),
-                    material: bg_material,
+                    mesh: assets.mesh.clone().into(),
+                    material: assets.background,
                     transform: Transform::from_xyz(0., -28., 1.),
                     ..Default::default()
                 },
                 HealthbarBackground,
             ))
             .with_children(|children| {
-                children.spawn((
-                    MaterialMesh2dBundle {
-                        mesh: fg_mesh.into(),
-                        material: fg_material,
-                        transform: Transform::from_xyz(
-                            -((max_width - remaining_width) / 2.),
-                            0.,
-                            2.,
-                        ),
-                        ..Default::default()
-                    },
-                    HealthbarForeground,
-                ));
+                foreground = children
+                    .spawn((
+                        MaterialMesh2dBundle {
+                            mesh: assets.mesh.into(),
+                            material: assets.foreground,
+                            transform: foreground_transform(entity_health.remaining_fraction()),
+                            ..Default::default()
+                        },
+                        HealthbarForeground,
+                    ))
+                    .id();
             })
             .id();
 
-        world.entity_mut(self.tracked_entity).add_child(bar);
-
         world
             .entity_mut(self.tracked_entity)
-            .insert(LinkedHealthbarId(bar));
+            .add_child(background)
+            .insert(LinkedHealthbarId {
+                background,
+                foreground,
+            });
     }
 }
 
+const BAR_WIDTH: f32 = 32.;
+const BAR_HEIGHT: f32 = 10.;
+
+/// Mesh and materials shared by every healthbar.
+#[derive(Resource, Clone)]
+pub struct HealthbarAssets {
+    mesh: Handle<Mesh>,
+    background: Handle<ColorMaterial>,
+    foreground: Handle<ColorMaterial>,
+}
+
+impl FromWorld for HealthbarAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mesh = world
+            .resource_mut::<Assets<Mesh>>()
+            .add(Rectangle::new(BAR_WIDTH, BAR_HEIGHT));
+        let mut materials = world.resource_mut::<Assets<ColorMaterial>>();
+
+        Self {
+            mesh,
+            background: materials.add(Color::RED),
+            foreground: materials.add(Color::GREEN),
+        }
+    }
+}
+
+/// Shrinks the full-width foreground towards the left edge of the bar.
+fn foreground_transform(remaining_fraction: f32) -> Transform {
+    Transform::from_xyz(-(BAR_WIDTH * (1. - remaining_fraction)) / 2., 0., 2.)
+        .with_scale(Vec3::new(remaining_fraction, 1., 1.))
+}
+
 #[derive(Component)]
-pub struct LinkedHealthbarId(Entity);
+pub struct LinkedHealthbarId {
+    background: Entity,
+    foreground: Entity,
+}
 
 pub struct DeleteHealthbar {
     target_entity: Entity,
@@ -119,22 +128,51 @@ impl DeleteHealthbar {
 
 impl Command for DeleteHealthbar {
     fn apply(self, world: &mut World) {
-        let linked_bar_entity = SystemState::<Query<&LinkedHealthbarId>>::new(world)
-            .get(world)
-            .get(self.target_entity)
-            .expect("Linked bar not found")
-            .0;
-
-        world.entity_mut(linked_bar_entity).despawn_recursive();
+        let linked_bar = world
+            .get_entity_mut(self.target_entity)
+            .and_then(|mut entity| entity.take::<LinkedHealthbarId>());
+
+        let bars = match linked_bar {
+            Some(linked_bar) => vec![linked_bar.background],
+            // The tracked entity was despawned without its children, find the bars it left behind
+            None => world
+                .query_filtered::<(Entity, &Parent), With<HealthbarBackground>>()
+                .iter(world)
+                .filter(|(_, parent)| parent.get() == self.target_entity)
+                .map(|(bar, _)| bar)
+                .collect(),
+        };
+
+        bars.into_iter().for_each(|bar| {
+            if let Some(bar) = world.get_entity_mut(bar) {
+                bar.despawn_recursive();
+            }
+        });
     }
 }
 
-fn update_healthbars(
-    q_with_bars: Query<Entity, (With<LinkedHealthbarId>, Changed<Health>)>,
+fn attach_healthbars(
+    q_new: Query<Entity, (Added<Health>, Without<LinkedHealthbarId>)>,
     mut commands: Commands,
 ) {
-    q_with_bars.iter().for_each(|entity| {
-        commands.add(DeleteHealthbar::new(entity));
+    q_new.iter().for_each(|entity| {
         commands.add(SpawnHealthbar::new(entity));
     })
 }
+
+fn update_healthbars(
+    q_with_bars: Query<(&Health, &LinkedHealthbarId), Changed<Health>>,
+    mut q_foreground: Query<&mut Transform, With<HealthbarForeground>>,
+) {
+    q_with_bars.iter().for_each(|(health, linked_bar)| {
+        if let Ok(mut transform) = q_foreground.get_mut(linked_bar.foreground) {
+            *transform = foreground_transform(health.remaining_fraction());
+        }
+    })
+}
+
+fn remove_healthbars(mut removed: RemovedComponents<Health>, mut commands: Commands) {
+    removed.read().for_each(|entity| {
+        commands.add(DeleteHealthbar::new(entity));
+    })
+}
@@ -0,0 +1,185 @@
+use bevy::prelude::*;
+
+use crate::{
+    game_state::{screen_text, GameState, RunTime},
+    prelude::*,
+    stats::RunStats,
+    xp_crumbs::PlayerLevel,
+};
+
+/// Screen-space overlay with the player's health, XP, level, run time and kills.
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_hud).add_systems(
+            Update,
+            (
+                show_hud.run_if(state_changed::<GameState>),
+                update_health.run_if(any_with_component::<Player>),
+                update_level.run_if(resource_changed::<PlayerLevel>),
+                update_run_time.run_if(resource_changed::<RunTime>),
+                update_kills.run_if(resource_changed::<RunStats>),
+            ),
+        );
+    }
+}
+
+#[derive(Component)]
+struct Hud;
+
+#[derive(Component)]
+struct HealthFill;
+
+#[derive(Component)]
+struct HealthText;
+
+#[derive(Component)]
+struct XpFill;
+
+#[derive(Component)]
+struct LevelText;
+
+#[derive(Component)]
+struct RunTimeText;
+
+#[derive(Component)]
+struct KillsText;
+
+/// A fixed-size bar whose fill is resized through its width percentage.
+fn spawn_bar(parent: &mut ChildBuilder, color: Color, fill_marker: impl Component) {
+    parent
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Px(240.),
+                height: Val::Px(16.),
+                ..Default::default()
+            },
+            background_color: Color::rgba(0., 0., 0., 0.6).into(),
+            ..Default::default()
+        })
+        .with_children(|bar| {
+            bar.spawn((
+                NodeBundle {
+                    style: Style {
+                        width: Val::Percent(0.),
+                        height: Val::Percent(100.),
+                        ..Default::default()
+                    },
+                    background_color: color.into(),
+                    ..Default::default()
+                },
+                fill_marker,
+            ));
+        });
+}
+
+fn spawn_hud(mut commands: Commands) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    padding: UiRect::all(Val::Px(12.)),
+                    justify_content: JustifyContent::SpaceBetween,
+                    ..Default::default()
+                },
+                visibility: Visibility::Hidden,
+                ..Default::default()
+            },
+            Hud,
+        ))
+        .with_children(|hud| {
+            hud.spawn(NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(4.),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .with_children(|left| {
+                spawn_bar(left, Color::RED, HealthFill);
+                left.spawn((screen_text("", 14.), HealthText));
+                spawn_bar(left, Color::CYAN, XpFill);
+                left.spawn((screen_text("", 18.), LevelText));
+            });
+
+            hud.spawn(NodeBundle {
+                style: Style {
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::FlexEnd,
+                    row_gap: Val::Px(4.),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .with_children(|right| {
+                right.spawn((screen_text("00:00", 24.), RunTimeText));
+                right.spawn((screen_text("Kills: 0", 18.), KillsText));
+            });
+        });
+}
+
+/// The HUD stays up over the pause, level up and game over screens, but not in the menus.
+fn show_hud(state: Res<State<GameState>>, mut q_hud: Query<&mut Visibility, With<Hud>>) {
+    let visible = matches!(
+        state.get(),
+        GameState::Playing | GameState::Paused | GameState::LevelUp | GameState::GameOver
+    );
+    q_hud.iter_mut().for_each(|mut visibility| {
+        *visibility = match visible {
+            true => Visibility::Inherited,
+            false => Visibility::Hidden,
+        };
+    });
+}
+
+fn update_health(
+    q_player: Query<&Health, (With<Player>, Changed<Health>)>,
+    mut q_fill: Query<&mut Style, With<HealthFill>>,
+    mut q_text: Query<&mut Text, With<HealthText>>,
+) {
+    let Ok(health) = q_player.get_single() else {
+        return;
+    };
+
+    if let Ok(mut style) = q_fill.get_single_mut() {
+        style.width = Val::Percent(health.remaining_fraction() * 100.);
+    }
+    if let Ok(mut text) = q_text.get_single_mut() {
+        text.sections[0].value = format!("{:.0} / {:.0}", health.cur_hp, health.max_hp);
+    }
+}
+
+fn update_level(
+    player_level: Res<PlayerLevel>,
+    mut q_fill: Query<&mut Style, With<XpFill>>,
+    mut q_text: Query<&mut Text, With<LevelText>>,
+) {
+    let progress = (player_level.cur_xp / player_level.next_level_threshold).clamp(0., 1.);
+
+    if let Ok(mut style) = q_fill.get_single_mut() {
+        style.width = Val::Percent(progress * 100.);
+    }
+    if let Ok(mut text) = q_text.get_single_mut() {
+        text.sections[0].value = format!("Level {}", player_level.cur_level);
+    }
+}
+
+fn update_run_time(run_time: Res<RunTime>, mut q_text: Query<&mut Text, With<RunTimeText>>) {
+    let elapsed = run_time.0.elapsed_secs();
+    if let Ok(mut text) = q_text.get_single_mut() {
+        text.sections[0].value = format!(
+            "{:02}:{:02}",
+            (elapsed / 60.) as u32,
+            (elapsed % 60.) as u32
+        );
+    }
+}
+
+fn update_kills(run_stats: Res<RunStats>, mut q_text: Query<&mut Text, With<KillsText>>) {
+    if let Ok(mut text) = q_text.get_single_mut() {
+        text.sections[0].value = format!("Kills: {}", run_stats.kills);
+    }
+}
@@ -2,9 +2,20 @@ use bevy::{
     ecs::system::{EntityCommand, SystemState},
     prelude::*,
 };
-use std::{fmt::Debug, marker::PhantomData};
+use std::{fmt::Debug, marker::PhantomData, time::Duration};
 
-use crate::{Died, EntityEvent, Healed, TookDamage};
+use crate::{game_state::GameState, Died, EntityEvent, Healed, TookDamage};
+
+pub struct HurtboxPlugin;
+
+impl Plugin for HurtboxPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            expire_invulnerability.run_if(in_state(GameState::Playing)),
+        );
+    }
+}
 
 #[derive(Component)]
 pub struct Hurtbox;
@@ -49,6 +60,7 @@ impl HurtboxBundle {
 
 pub struct TakeDamage<T: Component + Debug> {
     pub amount: f32,
+    pub critical: bool,
     marker: PhantomData<T>,
 }
 
@@ -56,9 +68,16 @@ impl<T: Component + Debug> TakeDamage<T> {
     pub fn new(amount: f32) -> Self {
         Self {
             amount,
+            critical: false,
             marker: Default::default(),
         }
     }
+
+    /// Marks the hit as a critical one, only changes how it is displayed.
+    pub fn critical(mut self) -> Self {
+        self.critical = true;
+        self
+    }
 }
 
 impl<T: Component + Debug> EntityCommand for TakeDamage<T> {
@@ -66,17 +85,26 @@ impl<T: Component + Debug> EntityCommand for TakeDamage<T> {
         let mut system_state = SystemState::<(
             EventWriter<EntityEvent<TookDamage, T>>,
             EventWriter<EntityEvent<Died, T>>,
-            Query<&mut Health, With<T>>,
+            Query<(&mut Health, &Transform), With<T>>,
         )>::new(world);
 
         let (mut damaged_writer, mut dead_writer, mut query) = system_state.get_mut(world);
-        let mut entity_health = query.get_mut(id).expect("Entity does not have health");
+        let (mut entity_health, transform) =
+            query.get_mut(id).expect("Entity does not have health");
+        let was_alive = entity_health.cur_hp > 0.;
 
         entity_health.take_damage(self.amount);
-        damaged_writer.send(EntityEvent::new(id));
+        damaged_writer.send(EntityEvent::new(
+            id,
+            TookDamage {
+                amount: self.amount,
+                critical: self.critical,
+                position: transform.translation.truncate(),
+            },
+        ));
 
-        if entity_health.cur_hp <= 0. {
-            dead_writer.send(EntityEvent::new(id));
+        if was_alive && entity_health.cur_hp <= 0. {
+            dead_writer.send(EntityEvent::new(id, Died));
         }
     }
 }
@@ -99,13 +127,66 @@ impl<T: Component + Debug> EntityCommand for Heal<T> {
     fn apply(self, id: Entity, world: &mut World) {
         let mut system_state = SystemState::<(
             EventWriter<EntityEvent<Healed, T>>,
-            Query<&mut Health, With<T>>,
+            Query<(&mut Health, &Transform), With<T>>,
         )>::new(world);
 
         let (mut healed_writer, mut query) = system_state.get_mut(world);
-        let mut entity_health = query.get_mut(id).expect("Entity does not have health");
+        let (mut entity_health, transform) =
+            query.get_mut(id).expect("Entity does not have health");
+        let hp_before = entity_health.cur_hp;
 
         entity_health.heal(self.amount);
-        healed_writer.send(EntityEvent::new(id));
+        healed_writer.send(EntityEvent::new(
+            id,
+            Healed {
+                amount: entity_health.cur_hp - hp_before,
+                position: transform.translation.truncate(),
+            },
+        ));
     }
 }
+
+/// Hits are ignored while this is on the entity. Removed once the timer runs out.
+#[derive(Component)]
+pub struct Invulnerable(Timer);
+
+impl Invulnerable {
+    pub fn new(duration: Duration) -> Self {
+        Self(Timer::new(duration, TimerMode::Once))
+    }
+}
+
+/// Grants invulnerability for `duration`, or restarts it if the entity already has some.
+pub struct GoInvulnerable {
+    pub duration: Duration,
+}
+
+impl GoInvulnerable {
+    pub fn new(duration: f32) -> Self {
+        Self {
+            duration: Duration::from_secs_f32(duration),
+        }
+    }
+}
+
+impl EntityCommand for GoInvulnerable {
+    fn apply(self, id: Entity, world: &mut World) {
+        if let Some(mut entity) = world.get_entity_mut(id) {
+            entity.insert(Invulnerable::new(self.duration));
+        }
+    }
+}
+
+fn expire_invulnerability(
+    time: Res<Time>,
+    mut q_invulnerable: Query<(Entity, &mut Invulnerable)>,
+    mut commands: Commands,
+) {
+    q_invulnerable
+        .iter_mut()
+        .for_each(|(entity, mut invulnerable)| {
+            if invulnerable.0.tick(time.delta()).finished() {
+                commands.entity(entity).remove::<Invulnerable>();
+            }
+        });
+}
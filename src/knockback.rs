@@ -0,0 +1,102 @@
+use std::time::Duration;
+
+use bevy::{ecs::system::EntityCommand, prelude::*};
+use bevy_xpbd_2d::prelude::*;
+
+use crate::game_state::GameState;
+
+/// Seconds an enemy stops steering after being knocked back by a projectile.
+pub const PROJECTILE_STUN: f32 = 0.15;
+
+/// Body size whose knockback mass is 1.
+const REFERENCE_SIZE: f32 = 32.;
+
+pub struct KnockbackPlugin;
+
+impl Plugin for KnockbackPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            expire_stuns.run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+/// Divides the velocity change of a knockback. Kept apart from the physics [`Mass`],
+/// which also counts sensors such as the player's collection radius.
+/// Entities without it have a mass of 1.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct KnockbackMass(pub f32);
+
+impl KnockbackMass {
+    /// Mass of a body `size` units wide, growing with its area.
+    pub fn from_size(size: f32) -> Self {
+        Self((size / REFERENCE_SIZE).powi(2))
+    }
+}
+
+/// AI steering is disabled while this is on the entity. Removed once the timer runs out.
+#[derive(Component)]
+pub struct Stunned(Timer);
+
+/// Pushes an entity away along `direction`, changing its velocity by `strength` divided by its
+/// [`KnockbackMass`], and optionally stuns it.
+pub struct Knockback {
+    pub direction: Vec2,
+    pub strength: f32,
+    pub stun: Duration,
+}
+
+impl Knockback {
+    pub fn new(direction: Vec2, strength: f32) -> Self {
+        Self {
+            direction,
+            strength,
+            stun: Duration::ZERO,
+        }
+    }
+
+    pub fn with_stun(mut self, seconds: f32) -> Self {
+        self.stun = Duration::from_secs_f32(seconds);
+        self
+    }
+}
+
+impl EntityCommand for Knockback {
+    fn apply(self, id: Entity, world: &mut World) {
+        let Some(mut entity) = world.get_entity_mut(id) else {
+            return;
+        };
+        let mass = entity
+            .get::<KnockbackMass>()
+            .map_or(1., |mass| mass.0.max(f32::EPSILON));
+
+        if let Some(mut velocity) = entity.get_mut::<LinearVelocity>() {
+            velocity.0 += self.direction.normalize_or_zero() * self.strength / mass;
+        }
+
+        if self.stun.is_zero() {
+            return;
+        }
+        // A weaker stun never cuts a longer one short
+        let remaining = entity
+            .get::<Stunned>()
+            .map_or(Duration::ZERO, |stunned| stunned.0.remaining());
+        entity.insert(Stunned(Timer::new(
+            self.stun.max(remaining),
+            TimerMode::Once,
+        )));
+    }
+}
+
+fn expire_stuns(
+    time: Res<Time>,
+    mut q_stunned: Query<(Entity, &mut Stunned)>,
+    mut commands: Commands,
+) {
+    q_stunned.iter_mut().for_each(|(entity, mut stunned)| {
+        if stunned.0.tick(time.delta()).finished() {
+            commands.entity(entity).remove::<Stunned>();
+        }
+    });
+}
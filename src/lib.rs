@@ -0,0 +1,56 @@
+use bevy::prelude::*;
+
+use crate::prelude::*;
+
+pub mod accessibility;
+pub mod bindings;
+pub mod blink;
+pub mod bot;
+pub mod bullet;
+pub mod character;
+pub mod cli;
+pub mod config;
+pub mod damage_numbers;
+pub mod director;
+pub mod enemy;
+pub mod game_state;
+pub mod headless;
+pub mod healthbar;
+pub mod hud;
+pub mod hurtbox;
+pub mod knockback;
+pub mod player;
+pub mod prelude;
+pub mod replay;
+pub mod rng;
+pub mod simulation;
+pub mod stats;
+pub mod status_effects;
+pub mod upgrades;
+pub mod user_config;
+pub mod weapon;
+pub mod xp_crumbs;
+
+/// The whole game with its presentation, meant to be added on top of [`DefaultPlugins`].
+/// Use [`SimulationPlugin`] with [`HeadlessPlugin`] instead to run without a window.
+pub struct GamePlugin;
+
+impl Plugin for GamePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            SimulationPlugin,
+            HealthbarPlugin,
+            HudPlugin,
+            DamageNumbersPlugin,
+            BindingsPlugin,
+            AccessibilityPlugin,
+            ConfigFilesPlugin,
+            ReplayPlugin,
+        ))
+        .add_systems(Startup, setup);
+    }
+}
+
+fn setup(mut commands: Commands) {
+    commands.spawn((Camera2dBundle::default(), MainCamera));
+}
@@ -1,47 +1,73 @@
-use crate::prelude::*;
-use bevy::prelude::*;
-use bevy_xpbd_2d::prelude::*;
-use blink::BlinkPlugin;
-use healthbar::SpawnHealthbar;
+use bevy::{app::AppExit, prelude::*};
+use bevy_physics_playground::{
+    bot::BotConnection,
+    cli::arg_value,
+    game_state::{GameState, RunTime},
+    headless::kite_nearest_enemy,
+    prelude::*,
+    replay::{Replay, ReplayPlayback, ReplayRecorder},
+    rng::{GameRng, RunSeed},
+    xp_crumbs::PlayerLevel,
+};
 
-mod blink;
-mod bullet;
-mod character;
-mod enemy;
-mod healthbar;
-mod hurtbox;
-mod player;
-mod prelude;
-mod xp_crumbs;
-
-fn setup(mut commands: Commands) {
-    commands.add(SpawnPlayer::default());
-    commands.spawn((Camera2dBundle::default(), MainCamera));
-}
-
-fn spawn_player_hotbar(mut commands: Commands, q_player: Query<Entity, With<Player>>) {
-    let player_entity = q_player.get_single().expect("Player not found");
-
-    commands.add(SpawnHealthbar {
-        tracked_entity: player_entity,
-    });
+fn report_headless_run(
+    run_time: Res<RunTime>,
+    player_level: Res<PlayerLevel>,
+    game_rng: Res<GameRng>,
+    mut ev_writer: EventWriter<AppExit>,
+) {
+    println!(
+        "Seed {} survived {:.1}s, reached level {}",
+        game_rng.seed(),
+        run_time.0.elapsed_secs(),
+        player_level.cur_level
+    );
+    ev_writer.send(AppExit);
 }
 
 fn main() {
-    App::new()
-        .add_plugins((
+    let mut app = App::new();
+    let headless = std::env::args().any(|arg| arg == "--headless");
+    let bot = match arg_value("--bot-port") {
+        Some(port) => Some(
+            BotConnection::tcp(("127.0.0.1", port.parse().expect("Port must be a number")))
+                .expect("Failed to accept bot connection"),
+        ),
+        None => std::env::args()
+            .any(|arg| arg == "--bot")
+            .then(BotConnection::stdio),
+    };
+
+    if let Some(connection) = bot {
+        app.add_plugins((MinimalPlugins, HeadlessPlugin, SimulationPlugin, BotPlugin))
+            .insert_resource(connection);
+    } else if headless {
+        app.add_plugins((MinimalPlugins, HeadlessPlugin, SimulationPlugin))
+            .add_systems(
+                FixedUpdate,
+                kite_nearest_enemy.run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(OnEnter(GameState::GameOver), report_headless_run);
+    } else {
+        app.add_plugins((
             DefaultPlugins,
-            PhysicsPlugins::default(),
+            GamePlugin,
             // PhysicsDebugPlugin::default(),
-            CharacterControllerPlugin,
-            BulletPlugin,
-            EnemyPlugin,
-            XpCrumbPlugin,
-            PlayerPlugin,
-            BlinkPlugin,
-            HealthbarPlugin,
-        ))
-        .add_systems(Startup, (setup, spawn_player_hotbar).chain())
-        .insert_resource(Gravity(Vec2::ZERO))
-        .run();
+        ));
+
+        if let Some(path) = arg_value("--record") {
+            app.insert_resource(ReplayRecorder::new(path));
+        }
+        if let Some(path) = arg_value("--replay") {
+            let replay = Replay::load(&path).expect("Failed to load replay");
+            let playback = ReplayPlayback::new(replay);
+            app.insert_resource(playback);
+        }
+    }
+
+    let seed = match app.world.get_resource::<ReplayPlayback>() {
+        Some(playback) => Some(playback.seed()),
+        None => arg_value("--seed").map(|seed| seed.parse().expect("Seed must be a number")),
+    };
+    app.insert_resource(RunSeed(seed)).run();
 }
@@ -1,19 +1,52 @@
-use crate::blink::GoInvulnerable;
-use crate::enemy::Invulnerable;
-use crate::hurtbox::TakeDamage;
+use crate::bindings::KeyBindings;
+use crate::blink::StartBlinking;
+use crate::bullet::{
+    OnHitEffect, Projectile, ProjectileDamage, ProjectileKnockback, ShotDirection,
+};
+use crate::character::MovementDampingFactor;
+use crate::config::{ConfigReloaded, ConfigSyncSet, EnemyConfig, MovementConfig, PlayerConfig};
+use crate::game_state::GameState;
+use crate::hurtbox::{GoInvulnerable, Invulnerable, TakeDamage};
+use crate::knockback::{Knockback, KnockbackMass};
 use crate::prelude::*;
-use bevy::ecs::system::{Command, EntityCommand, SystemState};
+use crate::status_effects::ApplyStatusEffect;
+use crate::weapon::{EquipWeapon, Weapon};
+use bevy::ecs::system::{Command, EntityCommand};
 use bevy::prelude::*;
 use bevy_xpbd_2d::prelude::*;
 
 #[derive(Component, Debug)]
 pub struct Player;
 
+/// Bonuses from the upgrades picked this run, kept apart from the config values they apply to so
+/// that a config reload can recompute them.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct PlayerUpgrades {
+    pub acceleration_multiplier: f32,
+    pub bonus_health: f32,
+    pub collection_radius_multiplier: f32,
+}
+
+impl Default for PlayerUpgrades {
+    fn default() -> Self {
+        Self {
+            acceleration_multiplier: 1.,
+            bonus_health: 0.,
+            collection_radius_multiplier: 1.,
+        }
+    }
+}
+
+/// Radius of the player's crumb collection sensor, kept alongside its [`Collider`].
+#[derive(Component, Debug, Clone, Copy)]
+pub struct CollectionRadius(pub f32);
+
 pub struct SpawnPlayer {
     position: Vec2,
     size: f32,
     health: f32,
     collection_radius: f32,
+    movement: MovementConfig,
 }
 
 pub struct PlayerPlugin;
@@ -24,30 +57,36 @@ impl Plugin for PlayerPlugin {
             .add_event::<EntityEvent<TookDamage, Player>>()
             .add_event::<EntityEvent<Healed, Player>>()
             .add_systems(
-                Update,
-                (handle_enemy_collisions,).run_if(on_event::<EnemyTouchedPlayerEvent>()),
+                FixedUpdate,
+                (
+                    (handle_enemy_collisions,).run_if(on_event::<EnemyTouchedPlayerEvent>()),
+                    (handle_projectile_hits,).run_if(on_event::<ProjectileHitEvent<Player>>()),
+                    (on_player_dead,).run_if(on_event::<EntityEvent<Died, Player>>()),
+                )
+                    .run_if(in_state(GameState::Playing)),
             )
             .add_systems(
                 Update,
-                (on_player_dead,).run_if(on_event::<EntityEvent<Died, Player>>()),
-            )
-            .add_systems(
-                Update,
-                (on_player_heal,).run_if(on_event::<EntityEvent<Healed, Player>>()),
-            )
-            .add_systems(
-                Update,
-                (on_player_hit,).run_if(on_event::<EntityEvent<TookDamage, Player>>()),
+                apply_player_config
+                    .after(ConfigSyncSet)
+                    .run_if(on_event::<ConfigReloaded<PlayerConfig>>()),
             );
     }
 }
 
 impl Default for SpawnPlayer {
     fn default() -> Self {
+        Self::from_config(&PlayerConfig::default())
+    }
+}
+
+impl SpawnPlayer {
+    pub fn from_config(config: &PlayerConfig) -> Self {
         Self {
-            size: 32.,
-            health: 15.,
-            collection_radius: 200.,
+            size: config.size,
+            health: config.health,
+            collection_radius: config.collection_radius,
+            movement: config.movement,
             position: Default::default(),
         }
     }
@@ -55,7 +94,10 @@ impl Default for SpawnPlayer {
 
 impl Command for SpawnPlayer {
     fn apply(self, world: &mut World) {
-        world
+        let input_map = world
+            .get_resource::<KeyBindings>()
+            .map_or_else(Action::default_input_map, KeyBindings::input_map);
+        let player = world
             .spawn((
                 SpriteBundle {
                     sprite: Sprite {
@@ -66,66 +108,107 @@ impl Command for SpawnPlayer {
                     transform: Transform::from_translation(self.position.extend(0.)),
                     ..Default::default()
                 },
-                CharacterControllerBundle::new(
-                    Collider::circle(self.size / 2.),
-                    Action::default_input_map(),
-                ),
+                CharacterControllerBundle::new(Collider::circle(self.size / 2.), input_map)
+                    .with_movement(self.movement.acceleration, self.movement.damping),
                 Player,
                 HurtboxBundle::new(self.health),
                 CollisionLayers::new(
                     GameLayer::Player,
-                    [GameLayer::Enemy, GameLayer::HealingCrumb],
+                    [
+                        GameLayer::Enemy,
+                        GameLayer::EnemyBullet,
+                        GameLayer::XpCrumb,
+                        GameLayer::HealingCrumb,
+                        GameLayer::MagnetCrumb,
+                    ],
                 ),
                 LockedAxes::ROTATION_LOCKED,
+                KnockbackMass::from_size(self.size),
+                PlayerUpgrades::default(),
             ))
             .with_children(|children| {
                 children.spawn((
                     Collider::circle(self.collection_radius),
+                    CollectionRadius(self.collection_radius),
                     Sensor,
-                    CollisionLayers::new(GameLayer::Player, [GameLayer::XpCrumb]),
+                    CollisionLayers::new(
+                        GameLayer::Player,
+                        [
+                            GameLayer::XpCrumb,
+                            GameLayer::HealingCrumb,
+                            GameLayer::MagnetCrumb,
+                        ],
+                    ),
                 ));
-            });
+            })
+            .id();
+
+        EquipWeapon(Weapon::pistol()).apply(player, world);
     }
 }
 
-pub struct GetPushed {
-    direction: Vec2,
-    velocity: f32,
-}
+/// Applies an edited player config to the live player, on top of the upgrades picked so far.
+#[allow(clippy::type_complexity)]
+fn apply_player_config(
+    mut ev_reader: EventReader<ConfigReloaded<PlayerConfig>>,
+    config: Res<PlayerConfig>,
+    mut q_player: Query<
+        (
+            &mut BaseAcceleration,
+            &mut MovementDampingFactor,
+            &mut Health,
+            &mut Collider,
+            &mut Sprite,
+            &mut KnockbackMass,
+            &PlayerUpgrades,
+            &Children,
+        ),
+        With<Player>,
+    >,
+    mut q_collection: Query<(&mut CollectionRadius, &mut Collider), Without<Player>>,
+) {
+    ev_reader.clear();
 
-impl GetPushed {
-    fn new(direction: Vec2, velocity: f32) -> Self {
-        Self {
-            direction,
-            velocity,
-        }
-    }
-}
+    let Ok((
+        mut acceleration,
+        mut damping,
+        mut health,
+        mut collider,
+        mut sprite,
+        mut mass,
+        upgrades,
+        children,
+    )) = q_player.get_single_mut()
+    else {
+        return;
+    };
 
-impl EntityCommand for GetPushed {
-    fn apply(self, id: Entity, world: &mut World) {
-        let time_delta = world
-            .get_resource::<Time>()
-            .expect("DIO HAS ACTIVATED THE WORLD (No time found)")
-            .delta_seconds();
-
-        let mut system_state = SystemState::<Query<&mut LinearVelocity>>::new(world);
-        let mut linear_velocity_query = system_state.get_mut(world);
-        let mut linear_velocity = linear_velocity_query
-            .get_mut(id)
-            .expect("Entity has no velocity");
-
-        let normalized_direction = self.direction.normalize();
-        linear_velocity.x += time_delta * self.velocity * normalized_direction.x;
-        linear_velocity.y += time_delta * self.velocity * normalized_direction.y;
-    }
+    acceleration.0 = config.movement.acceleration * upgrades.acceleration_multiplier;
+    damping.0 = config.movement.damping;
+    let max_hp = config.health + upgrades.bonus_health;
+    // An edit should never be what kills the player
+    health.cur_hp = (health.cur_hp + max_hp - health.max_hp).max(1.).min(max_hp);
+    health.max_hp = max_hp;
+    *collider = Collider::circle(config.size / 2.);
+    sprite.custom_size = Some(Vec2::splat(config.size));
+    *mass = KnockbackMass::from_size(config.size);
+
+    let collection_radius = config.collection_radius * upgrades.collection_radius_multiplier;
+    children.iter().for_each(|child| {
+        if let Ok((mut radius, mut collider)) = q_collection.get_mut(*child) {
+            radius.0 = collection_radius;
+            *collider = Collider::circle(radius.0);
+        }
+    });
 }
 
+#[allow(clippy::type_complexity)]
 fn handle_enemy_collisions(
     mut ev_reader: EventReader<EnemyTouchedPlayerEvent>,
     mut commands: Commands,
     q_player: Query<(Entity, &Transform, Option<&Invulnerable>), (With<Player>, Without<Enemy>)>,
     q_enemies: Query<&Transform, With<Enemy>>,
+    enemy_config: Res<EnemyConfig>,
 ) {
     if let Ok((player_entity, player_tr, player_invulnerable)) = q_player.get_single() {
         let mut applied_dmg = false;
@@ -133,8 +216,9 @@ fn handle_enemy_collisions(
             if !applied_dmg && !player_invulnerable.is_some() {
                 commands
                     .entity(player_entity)
-                    .add(TakeDamage::<Player>::new(5.))
-                    .add(GoInvulnerable::new(2., 5));
+                    .add(TakeDamage::<Player>::new(enemy_config.contact_damage))
+                    .add(GoInvulnerable::new(2.))
+                    .add(StartBlinking::new(2., 5));
                 applied_dmg = true;
             }
 
@@ -142,21 +226,61 @@ fn handle_enemy_collisions(
                 .get(ev.enemy)
                 .expect("Enemy was deleted before collision could be handled");
             let push_direction = (player_tr.translation - enemy_tr.translation).truncate();
-            commands
-                .entity(player_entity)
-                .add(GetPushed::new(push_direction, 10000.));
+            commands.entity(player_entity).add(Knockback::new(
+                push_direction,
+                enemy_config.contact_knockback,
+            ));
         });
     }
 }
 
-fn on_player_dead(mut ev_reader: EventReader<EntityEvent<Died, Player>>) {
-    dbg!("Player is dead and we killed him");
-}
+fn handle_projectile_hits(
+    mut ev_reader: EventReader<ProjectileHitEvent<Player>>,
+    mut commands: Commands,
+    q_player: Query<Option<&Invulnerable>, With<Player>>,
+    q_projectiles: Query<
+        (
+            &ProjectileDamage,
+            &ProjectileKnockback,
+            &ShotDirection,
+            Option<&OnHitEffect>,
+        ),
+        With<Projectile>,
+    >,
+) {
+    let mut applied_dmg = false;
+    ev_reader.read().for_each(
+        |ProjectileHitEvent::<Player> {
+             projectile, target, ..
+         }| {
+            let player_invulnerable = q_player
+                .get(*target)
+                .expect("Player was deleted before the hit could be handled");
+            let (projectile_damage, knockback, direction, on_hit) = q_projectiles
+                .get(*projectile)
+                .expect("Failed to find projectile");
 
-fn on_player_hit(mut ev_reader: EventReader<EntityEvent<TookDamage, Player>>) {
-    dbg!("Skill issue");
+            if !applied_dmg && player_invulnerable.is_none() {
+                commands
+                    .entity(*target)
+                    .add(TakeDamage::<Player>::new(projectile_damage.0))
+                    .add(GoInvulnerable::new(2.))
+                    .add(StartBlinking::new(2., 5))
+                    .add(Knockback::new(direction.0, knockback.0));
+                if let Some(on_hit) = on_hit {
+                    commands.entity(*target).add(ApplyStatusEffect(on_hit.0));
+                }
+                applied_dmg = true;
+            }
+            commands.entity(*projectile).add(RemoveEntity);
+        },
+    );
 }
 
-fn on_player_heal(mut ev_reader: EventReader<EntityEvent<Healed, Player>>) {
-    dbg!("Skill solution");
+fn on_player_dead(
+    mut ev_reader: EventReader<EntityEvent<Died, Player>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    ev_reader.clear();
+    next_state.set(GameState::GameOver);
 }
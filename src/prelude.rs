@@ -4,8 +4,8 @@ use std::fmt::Debug;
 use std::marker::PhantomData;
 
 pub use crate::character::{
-    Action, CharacterControllerBundle, CharacterControllerPlugin, MovementAcceleration,
-    MovementBundle, PlayerPosition,
+    Action, BaseAcceleration, CharacterControllerBundle, CharacterControllerPlugin,
+    MovementAcceleration, MovementBundle, PlayerPosition,
 };
 
 pub use crate::enemy::{EnemyPlugin, EnemyTouchedPlayerEvent};
@@ -14,12 +14,34 @@ pub use crate::bullet::{BulletPlugin, GameLayer, ProjectileHitEvent};
 
 pub use crate::hurtbox::{Health, HurtboxBundle};
 
-pub use crate::xp_crumbs::{XpCrumbBundle, XpCrumbPlugin};
+pub use crate::xp_crumbs::XpCrumbPlugin;
 
 pub use crate::player::{Player, PlayerPlugin, SpawnPlayer};
 
 pub use crate::healthbar::HealthbarPlugin;
 
+pub use crate::{
+    accessibility::AccessibilityPlugin,
+    bindings::BindingsPlugin,
+    blink::BlinkPlugin,
+    bot::BotPlugin,
+    config::{ConfigFilesPlugin, ConfigPlugin},
+    damage_numbers::DamageNumbersPlugin,
+    director::DirectorPlugin,
+    game_state::GameStatePlugin,
+    headless::HeadlessPlugin,
+    hud::HudPlugin,
+    hurtbox::HurtboxPlugin,
+    knockback::KnockbackPlugin,
+    replay::ReplayPlugin,
+    rng::RngPlugin,
+    simulation::SimulationPlugin,
+    stats::StatsPlugin,
+    status_effects::StatusEffectPlugin,
+    upgrades::UpgradePlugin,
+    GamePlugin,
+};
+
 #[derive(Component)]
 pub struct MainCamera;
 
@@ -31,18 +53,29 @@ pub struct RemoveEntity;
 impl EntityCommand for RemoveEntity {
     fn apply(self, id: Entity, world: &mut World) {
         // dbg!("Removed", id);
-        world.entity_mut(id).despawn_recursive();
+        if let Some(entity_mut) = world.get_entity_mut(id) {
+            entity_mut.despawn_recursive();
+        }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub struct Died;
 
-#[derive(Debug)]
-pub struct TookDamage;
+#[derive(Debug, Clone, Copy)]
+pub struct TookDamage {
+    pub amount: f32,
+    pub critical: bool,
+    /// Where the entity was when it got hit.
+    pub position: Vec2,
+}
 
-#[derive(Debug)]
-pub struct Healed;
+#[derive(Debug, Clone, Copy)]
+pub struct Healed {
+    /// Health actually restored, without the part that would go over the maximum.
+    pub amount: f32,
+    pub position: Vec2,
+}
 
 pub trait EventType: Debug {}
 
@@ -53,16 +86,16 @@ impl EventType for Healed {}
 #[derive(Event)]
 pub struct EntityEvent<EvT: EventType, EnT: Component + Debug> {
     pub entity: Entity,
-    entity_type: PhantomData<EvT>,
-    event_type: PhantomData<EnT>,
+    pub data: EvT,
+    entity_type: PhantomData<EnT>,
 }
 
 impl<EvT: EventType, EnT: Component + Debug> EntityEvent<EvT, EnT> {
-    pub fn new(entity: Entity) -> Self {
+    pub fn new(entity: Entity, data: EvT) -> Self {
         Self {
             entity,
+            data,
             entity_type: Default::default(),
-            event_type: Default::default(),
         }
     }
 }
@@ -0,0 +1,279 @@
+use std::{fmt, fs, path::PathBuf, time::Duration};
+
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+use leafwing_input_manager::{plugin::InputManagerSystem, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bullet::CursorPosition,
+    game_state::GameState,
+    prelude::*,
+    rng::GameRng,
+    upgrades::{ChooseUpgrade, Upgrade, UpgradeChosen},
+};
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PreUpdate,
+            (
+                feed_replay_input
+                    .in_set(InputManagerSystem::ManualControl)
+                    .run_if(resource_exists::<ReplayPlayback>),
+                // The fixed update of this frame sees this input, with the aim from last frame
+                record_input
+                    .after(InputManagerSystem::ManualControl)
+                    .run_if(resource_exists::<ReplayRecorder>),
+            ),
+        )
+        .add_systems(
+            Update,
+            (
+                record_upgrades
+                    .run_if(resource_exists::<ReplayRecorder>)
+                    .run_if(on_event::<UpgradeChosen>()),
+                (
+                    choose_replay_upgrade.run_if(in_state(GameState::LevelUp)),
+                    resume_replay.run_if(in_state(GameState::Paused)),
+                )
+                    .run_if(resource_exists::<ReplayPlayback>),
+            ),
+        )
+        .add_systems(
+            Last,
+            (
+                record_frame
+                    .run_if(resource_exists::<ReplayRecorder>)
+                    .run_if(in_state(GameState::Playing)),
+                advance_replay.run_if(resource_exists::<ReplayPlayback>),
+            ),
+        )
+        .add_systems(
+            OnEnter(GameState::MainMenu),
+            (
+                save_replay.run_if(resource_exists::<ReplayRecorder>),
+                start_replay.run_if(resource_exists::<ReplayPlayback>),
+            ),
+        )
+        .add_systems(
+            OnEnter(GameState::GameOver),
+            save_replay.run_if(resource_exists::<ReplayRecorder>),
+        );
+    }
+}
+
+/// Player input of a single frame spent in [`GameState::Playing`], as the fixed update of that
+/// frame reads it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayFrame {
+    /// Real time that passed since the previous frame.
+    pub delta: Duration,
+    pub action_state: ActionState<Action>,
+    pub cursor: Vec2,
+    /// Whether the game was paused at the end of this frame.
+    pub paused: bool,
+}
+
+/// Everything needed to reproduce a run.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Replay {
+    pub seed: u64,
+    pub frames: Vec<ReplayFrame>,
+    /// Upgrades in the order they were picked.
+    pub upgrades: Vec<Upgrade>,
+}
+
+impl Replay {
+    pub fn load(path: &str) -> Result<Self, ReplayError> {
+        let contents = fs::read_to_string(path)?;
+        Ok(ron::from_str(&contents)?)
+    }
+
+    pub fn save(&self, path: &PathBuf) -> Result<(), ReplayError> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, ron::to_string(self)?)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(std::io::Error),
+    Parse(ron::error::SpannedError),
+    Serialize(ron::Error),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Io(err) => write!(f, "could not access replay file: {err}"),
+            ReplayError::Parse(err) => write!(f, "could not parse replay: {err}"),
+            ReplayError::Serialize(err) => write!(f, "could not serialize replay: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl From<std::io::Error> for ReplayError {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<ron::error::SpannedError> for ReplayError {
+    fn from(err: ron::error::SpannedError) -> Self {
+        Self::Parse(err)
+    }
+}
+
+impl From<ron::Error> for ReplayError {
+    fn from(err: ron::Error) -> Self {
+        Self::Serialize(err)
+    }
+}
+
+/// Records every run and writes it to `path` once the run is over.
+#[derive(Resource)]
+pub struct ReplayRecorder {
+    path: PathBuf,
+    replay: Replay,
+    /// Input of the current frame, kept once the game state of the frame is known.
+    pending: Option<ReplayFrame>,
+}
+
+impl ReplayRecorder {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            replay: Replay::default(),
+            pending: None,
+        }
+    }
+}
+
+/// Plays back a recorded run instead of live input. Removed once the replay runs out.
+#[derive(Resource)]
+pub struct ReplayPlayback {
+    replay: Replay,
+    frame: usize,
+    upgrade: usize,
+}
+
+impl ReplayPlayback {
+    pub fn new(replay: Replay) -> Self {
+        Self {
+            replay,
+            frame: 0,
+            upgrade: 0,
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.replay.seed
+    }
+}
+
+fn record_input(
+    time: Res<Time<Real>>,
+    q_player: Query<&ActionState<Action>, With<Player>>,
+    cursor_position: Res<CursorPosition>,
+    mut recorder: ResMut<ReplayRecorder>,
+) {
+    recorder.pending = Some(ReplayFrame {
+        delta: time.delta(),
+        action_state: q_player.get_single().cloned().unwrap_or_default(),
+        cursor: cursor_position.0,
+        paused: false,
+    });
+}
+
+fn record_frame(next_state: Res<NextState<GameState>>, mut recorder: ResMut<ReplayRecorder>) {
+    if let Some(mut frame) = recorder.pending.take() {
+        frame.paused = next_state.0 == Some(GameState::Paused);
+        recorder.replay.frames.push(frame);
+    }
+}
+
+fn record_upgrades(
+    mut ev_reader: EventReader<UpgradeChosen>,
+    mut recorder: ResMut<ReplayRecorder>,
+) {
+    ev_reader.read().for_each(|UpgradeChosen(upgrade)| {
+        recorder.replay.upgrades.push(*upgrade);
+    });
+}
+
+fn save_replay(game_rng: Res<GameRng>, mut recorder: ResMut<ReplayRecorder>) {
+    if recorder.replay.frames.is_empty() {
+        return;
+    }
+
+    recorder.replay.seed = game_rng.seed();
+    match recorder.replay.save(&recorder.path) {
+        Ok(()) => info!("Saved replay to {}", recorder.path.display()),
+        Err(err) => error!("Failed to save replay: {err}"),
+    }
+    recorder.replay = Replay::default();
+}
+
+fn start_replay(playback: Res<ReplayPlayback>, mut next_state: ResMut<NextState<GameState>>) {
+    if playback.frame == 0 {
+        next_state.set(GameState::Playing);
+    }
+}
+
+fn feed_replay_input(
+    playback: Res<ReplayPlayback>,
+    mut q_player: Query<&mut ActionState<Action>, With<Player>>,
+    mut cursor_position: ResMut<CursorPosition>,
+) {
+    let Some(frame) = playback.replay.frames.get(playback.frame) else {
+        return;
+    };
+
+    if let Ok(mut action_state) = q_player.get_single_mut() {
+        *action_state = frame.action_state.clone();
+    }
+    cursor_position.0 = frame.cursor;
+}
+
+fn choose_replay_upgrade(mut playback: ResMut<ReplayPlayback>, mut commands: Commands) {
+    if let Some(upgrade) = playback.replay.upgrades.get(playback.upgrade).copied() {
+        playback.upgrade += 1;
+        commands.add(ChooseUpgrade(upgrade));
+    }
+}
+
+fn resume_replay(mut next_state: ResMut<NextState<GameState>>) {
+    next_state.set(GameState::Playing);
+}
+
+/// Steps to the next recorded frame and makes the next frame last exactly as long as it did.
+fn advance_replay(
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut playback: ResMut<ReplayPlayback>,
+    mut time_strategy: ResMut<TimeUpdateStrategy>,
+    mut commands: Commands,
+) {
+    if *state.get() == GameState::Playing {
+        if playback.replay.frames[playback.frame].paused {
+            next_state.set(GameState::Paused);
+        }
+        playback.frame += 1;
+    }
+
+    match playback.replay.frames.get(playback.frame) {
+        Some(frame) => *time_strategy = TimeUpdateStrategy::ManualDuration(frame.delta),
+        None => {
+            info!("Replay finished");
+            *time_strategy = TimeUpdateStrategy::Automatic;
+            commands.remove_resource::<ReplayPlayback>();
+        }
+    }
+}
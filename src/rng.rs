@@ -0,0 +1,57 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use rand::{rngs::StdRng, SeedableRng};
+
+pub struct RngPlugin;
+
+impl Plugin for RngPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunSeed>()
+            .insert_resource(GameRng::new(rand::random()));
+    }
+}
+
+/// Seed used for the next run. A fresh one is drawn for every run when `None`.
+#[derive(Resource, Debug, Default, Clone, Copy)]
+pub struct RunSeed(pub Option<u64>);
+
+/// Independent random streams, so that e.g. firing more shots does not change where enemies spawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RngStream {
+    PlayerWeapons,
+    EnemyWeapons,
+    Director,
+    Drops,
+    Upgrades,
+    Crits,
+}
+
+/// The only source of randomness for gameplay systems. A run is reproducible from its seed.
+#[derive(Resource, Debug)]
+pub struct GameRng {
+    seed: u64,
+    streams: HashMap<RngStream, StdRng>,
+}
+
+impl GameRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            streams: HashMap::new(),
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn stream(&mut self, stream: RngStream) -> &mut StdRng {
+        let seed = self.seed;
+        self.streams.entry(stream).or_insert_with(|| {
+            // Spread the streams apart so that neighbouring seeds don't share sequences
+            let offset = (stream as u64 + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+            StdRng::seed_from_u64(seed ^ offset)
+        })
+    }
+}
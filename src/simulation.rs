@@ -0,0 +1,39 @@
+use bevy::{ecs::schedule::ExecutorKind, prelude::*};
+use bevy_xpbd_2d::prelude::*;
+
+use crate::{
+    blink::BlinkPlugin, config::ConfigPlugin, director::DirectorPlugin,
+    game_state::GameStatePlugin, prelude::*, rng::RngPlugin, upgrades::UpgradePlugin,
+};
+
+/// The game rules and physics, without anything that needs a window, renderer or asset files.
+pub struct SimulationPlugin;
+
+impl Plugin for SimulationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            PhysicsPlugins::new(FixedPostUpdate),
+            CharacterControllerPlugin,
+            BulletPlugin,
+            EnemyPlugin,
+            DirectorPlugin,
+            XpCrumbPlugin,
+            PlayerPlugin,
+            BlinkPlugin,
+            (HurtboxPlugin, KnockbackPlugin, StatusEffectPlugin),
+            GameStatePlugin,
+            ConfigPlugin,
+            UpgradePlugin,
+            RngPlugin,
+            StatsPlugin,
+        ))
+        // Unordered systems would otherwise run in whatever order the threads pick them up,
+        // and a seed or a replay has to play out the same way every time
+        .edit_schedule(FixedUpdate, |schedule| {
+            schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+        })
+        .insert_resource(Gravity(Vec2::ZERO))
+        .insert_resource(Time::<Fixed>::from_hz(60.))
+        .insert_resource(Time::new_with(Physics::fixed_once_hz(60.)));
+    }
+}
@@ -0,0 +1,43 @@
+use bevy::prelude::*;
+
+use crate::{game_state::GameState, prelude::*};
+
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(RunStats::default()).add_systems(
+            FixedUpdate,
+            (
+                count_kills.run_if(on_event::<EntityEvent<Died, Enemy>>()),
+                track_damage_taken.run_if(on_event::<EntityEvent<TookDamage, Player>>()),
+            )
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+/// Totals for the current run, reset when a run starts.
+#[derive(Resource, Debug, Default, Clone)]
+pub struct RunStats {
+    pub kills: u32,
+    pub damage_taken: f32,
+    pub xp_collected: f32,
+}
+
+fn count_kills(
+    mut dead_reader: EventReader<EntityEvent<Died, Enemy>>,
+    mut run_stats: ResMut<RunStats>,
+) {
+    run_stats.kills += dead_reader.read().count() as u32;
+}
+
+fn track_damage_taken(
+    mut damaged_reader: EventReader<EntityEvent<TookDamage, Player>>,
+    mut run_stats: ResMut<RunStats>,
+) {
+    run_stats.damage_taken += damaged_reader
+        .read()
+        .map(|EntityEvent { data, .. }| data.amount)
+        .sum::<f32>();
+}
@@ -0,0 +1,247 @@
+use std::fmt::Debug;
+
+use bevy::{ecs::system::EntityCommand, prelude::*};
+
+use crate::{
+    game_state::GameState,
+    hurtbox::{Hurtbox, TakeDamage},
+    prelude::*,
+};
+
+pub struct StatusEffectPlugin;
+
+impl Plugin for StatusEffectPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (
+                tick_status_effects::<Enemy>,
+                tick_status_effects::<Player>,
+                apply_movement_effects,
+            )
+                .chain()
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StatusEffectKind {
+    Burn,
+    Poison,
+    Slow,
+    Freeze,
+}
+
+/// What happens when an effect is applied to an entity that already has one of the same kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stacking {
+    /// Restart the existing effect.
+    Refresh,
+    /// Add another independent instance, up to `max`. Past that the oldest one is restarted.
+    Stack { max: usize },
+    /// Keep the existing effect as it is.
+    Ignore,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StatusEffect {
+    pub kind: StatusEffectKind,
+    /// Seconds the effect lasts.
+    pub duration: f32,
+    /// Seconds between damage ticks.
+    pub tick_interval: f32,
+    pub damage_per_tick: f32,
+    /// Scales the [`BaseAcceleration`] while the effect lasts.
+    pub movement_multiplier: f32,
+    pub stacking: Stacking,
+}
+
+impl StatusEffect {
+    pub fn burn() -> Self {
+        Self {
+            kind: StatusEffectKind::Burn,
+            duration: 3.,
+            tick_interval: 0.5,
+            damage_per_tick: 1.,
+            movement_multiplier: 1.,
+            stacking: Stacking::Refresh,
+        }
+    }
+
+    pub fn poison() -> Self {
+        Self {
+            kind: StatusEffectKind::Poison,
+            duration: 5.,
+            tick_interval: 1.,
+            damage_per_tick: 0.5,
+            movement_multiplier: 1.,
+            stacking: Stacking::Stack { max: 5 },
+        }
+    }
+
+    pub fn slow() -> Self {
+        Self {
+            kind: StatusEffectKind::Slow,
+            duration: 2.,
+            tick_interval: 1.,
+            damage_per_tick: 0.,
+            movement_multiplier: 0.5,
+            stacking: Stacking::Refresh,
+        }
+    }
+
+    pub fn freeze() -> Self {
+        Self {
+            kind: StatusEffectKind::Freeze,
+            duration: 1.,
+            tick_interval: 1.,
+            damage_per_tick: 0.,
+            movement_multiplier: 0.,
+            stacking: Stacking::Ignore,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct ActiveEffect {
+    effect: StatusEffect,
+    duration: Timer,
+    tick: Timer,
+}
+
+impl ActiveEffect {
+    fn new(effect: StatusEffect) -> Self {
+        Self {
+            effect,
+            duration: Timer::from_seconds(effect.duration, TimerMode::Once),
+            tick: Timer::from_seconds(effect.tick_interval, TimerMode::Repeating),
+        }
+    }
+
+    fn restart(&mut self) {
+        self.duration.reset();
+        self.tick.reset();
+    }
+}
+
+/// Effects currently running on an entity. Removed once the last one runs out.
+#[derive(Component, Debug, Default)]
+pub struct StatusEffects {
+    active: Vec<ActiveEffect>,
+}
+
+impl StatusEffects {
+    /// Number of running instances of `kind`.
+    pub fn count(&self, kind: StatusEffectKind) -> usize {
+        self.active
+            .iter()
+            .filter(|active| active.effect.kind == kind)
+            .count()
+    }
+
+    pub fn has(&self, kind: StatusEffectKind) -> bool {
+        self.count(kind) > 0
+    }
+
+    fn add(&mut self, effect: StatusEffect) {
+        let count = self.count(effect.kind);
+        let same_kind = self
+            .active
+            .iter_mut()
+            .filter(|active| active.effect.kind == effect.kind);
+
+        match effect.stacking {
+            _ if count == 0 => self.active.push(ActiveEffect::new(effect)),
+            Stacking::Stack { max } if count < max => self.active.push(ActiveEffect::new(effect)),
+            Stacking::Refresh => same_kind.for_each(ActiveEffect::restart),
+            Stacking::Stack { .. } => {
+                if let Some(oldest) = same_kind.min_by_key(|active| active.duration.remaining()) {
+                    oldest.restart();
+                }
+            }
+            Stacking::Ignore => {}
+        }
+    }
+
+    fn movement_multiplier(&self) -> f32 {
+        self.active
+            .iter()
+            .map(|active| active.effect.movement_multiplier)
+            .product()
+    }
+}
+
+/// Kinds of effects that are never applied to the entity.
+#[derive(Component, Debug, Clone, Default)]
+pub struct StatusImmunities(pub Vec<StatusEffectKind>);
+
+/// Applies a status effect to an entity with a [`Hurtbox`], unless it is immune.
+pub struct ApplyStatusEffect(pub StatusEffect);
+
+impl EntityCommand for ApplyStatusEffect {
+    fn apply(self, id: Entity, world: &mut World) {
+        let Some(mut entity) = world.get_entity_mut(id) else {
+            return;
+        };
+        let immune = entity
+            .get::<StatusImmunities>()
+            .is_some_and(|immunities| immunities.0.contains(&self.0.kind));
+        if !entity.contains::<Hurtbox>() || immune {
+            return;
+        }
+
+        if !entity.contains::<StatusEffects>() {
+            entity.insert(StatusEffects::default());
+        }
+        entity
+            .get_mut::<StatusEffects>()
+            .expect("Status effects were just inserted")
+            .add(self.0);
+    }
+}
+
+fn tick_status_effects<T: Component + Debug>(
+    time: Res<Time>,
+    mut q_affected: Query<(Entity, &mut StatusEffects, &Health), With<T>>,
+    mut commands: Commands,
+) {
+    q_affected
+        .iter_mut()
+        .for_each(|(entity, mut effects, health)| {
+            let damage = effects
+                .active
+                .iter_mut()
+                .map(|active| {
+                    active.duration.tick(time.delta());
+                    let ticks = active.tick.tick(time.delta()).times_finished_this_tick();
+                    ticks as f32 * active.effect.damage_per_tick
+                })
+                .sum::<f32>();
+            effects.active.retain(|active| !active.duration.finished());
+
+            // Dying entities are despawned later in the tick, don't queue more damage for them
+            if damage > 0. && health.cur_hp > 0. {
+                commands.entity(entity).add(TakeDamage::<T>::new(damage));
+            }
+            if effects.active.is_empty() {
+                commands.entity(entity).remove::<StatusEffects>();
+            }
+        });
+}
+
+/// Derives the acceleration from the base one, slowed down by whatever effects are running.
+fn apply_movement_effects(
+    mut q_movers: Query<(
+        &BaseAcceleration,
+        &mut MovementAcceleration,
+        Option<&StatusEffects>,
+    )>,
+) {
+    q_movers
+        .iter_mut()
+        .for_each(|(base, mut acceleration, effects)| {
+            let multiplier = effects.map_or(1., StatusEffects::movement_multiplier);
+            acceleration.0 = base.0 * multiplier;
+        });
+}
@@ -0,0 +1,303 @@
+use bevy::{
+    ecs::system::{Command, EntityCommand},
+    prelude::*,
+};
+use bevy_xpbd_2d::prelude::*;
+use rand::{
+    distributions::{Distribution, WeightedIndex},
+    Rng,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bullet::{ProjectileDamage, ShootCooldown},
+    game_state::{despawn_screen, screen_root, screen_text, GameState},
+    hurtbox::Heal,
+    player::{CollectionRadius, PlayerUpgrades},
+    prelude::*,
+    replay::ReplayPlayback,
+    rng::{GameRng, RngStream},
+    weapon::{EquipWeapon, Weapon},
+    xp_crumbs::LevelUpEvent,
+};
+
+pub struct UpgradePlugin;
+
+impl Plugin for UpgradePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(UpgradePool::default())
+            .insert_resource(UpgradeChoices::default())
+            .add_event::<UpgradeChosen>()
+            .add_systems(
+                FixedUpdate,
+                offer_upgrades
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(on_event::<LevelUpEvent>()),
+            )
+            .add_systems(
+                Update,
+                choose_upgrade
+                    .run_if(in_state(GameState::LevelUp))
+                    .run_if(not(resource_exists::<ReplayPlayback>)),
+            )
+            .add_systems(OnEnter(GameState::LevelUp), spawn_level_up_screen)
+            .add_systems(OnExit(GameState::LevelUp), despawn_screen::<LevelUpScreen>);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Upgrade {
+    Damage,
+    FireRate,
+    MoveSpeed,
+    MaxHealth,
+    PickupRadius,
+    Recovery,
+    Shotgun,
+    Nova,
+}
+
+impl Upgrade {
+    pub fn description(&self) -> &'static str {
+        match self {
+            Upgrade::Damage => "Heavier rounds: +25% damage",
+            Upgrade::FireRate => "Quick trigger: -15% shot cooldown",
+            Upgrade::MoveSpeed => "Light feet: +10% movement speed",
+            Upgrade::MaxHealth => "Thick skin: +5 max health",
+            Upgrade::PickupRadius => "Magnetism: +25% pickup radius",
+            Upgrade::Recovery => "Second wind: restore 10 health",
+            Upgrade::Shotgun => "New weapon: shotgun",
+            Upgrade::Nova => "New weapon: nova",
+        }
+    }
+
+    /// Weapon unlocks can only be picked once per run.
+    pub fn is_unique(&self) -> bool {
+        matches!(self, Upgrade::Shotgun | Upgrade::Nova)
+    }
+}
+
+/// Upgrades that can be offered on level up, along with their relative weights.
+#[derive(Resource)]
+pub struct UpgradePool(pub Vec<(Upgrade, u32)>);
+
+impl Default for UpgradePool {
+    fn default() -> Self {
+        Self(vec![
+            (Upgrade::Damage, 10),
+            (Upgrade::FireRate, 10),
+            (Upgrade::MoveSpeed, 8),
+            (Upgrade::MaxHealth, 8),
+            (Upgrade::PickupRadius, 6),
+            (Upgrade::Recovery, 4),
+            (Upgrade::Shotgun, 3),
+            (Upgrade::Nova, 3),
+        ])
+    }
+}
+
+impl UpgradePool {
+    /// Draws up to `count` distinct upgrades from the pool.
+    pub fn roll(&self, count: usize, rng: &mut impl Rng) -> Vec<Upgrade> {
+        let mut candidates = self.0.clone();
+        let mut rolled = Vec::with_capacity(count);
+
+        while rolled.len() < count && !candidates.is_empty() {
+            let Ok(distribution) = WeightedIndex::new(candidates.iter().map(|(_, weight)| *weight))
+            else {
+                break;
+            };
+            rolled.push(candidates.swap_remove(distribution.sample(rng)).0);
+        }
+
+        rolled
+    }
+}
+
+/// Upgrades currently offered to the player.
+#[derive(Resource)]
+pub struct UpgradeChoices {
+    pub level: u32,
+    pub offered: Vec<Upgrade>,
+    pub count: usize,
+}
+
+impl Default for UpgradeChoices {
+    fn default() -> Self {
+        Self {
+            level: 1,
+            offered: Vec::new(),
+            count: 3,
+        }
+    }
+}
+
+pub struct ApplyUpgrade(pub Upgrade);
+
+impl EntityCommand for ApplyUpgrade {
+    fn apply(self, id: Entity, world: &mut World) {
+        match self.0 {
+            Upgrade::Damage => {
+                held_weapons(id, world).into_iter().for_each(|weapon| {
+                    if let Some(mut damage) = world.get_mut::<ProjectileDamage>(weapon) {
+                        damage.0 *= 1.25;
+                    }
+                });
+            }
+            Upgrade::FireRate => {
+                held_weapons(id, world).into_iter().for_each(|weapon| {
+                    if let Some(mut cooldown) = world.get_mut::<ShootCooldown>(weapon) {
+                        let new_duration = cooldown.0.duration().mul_f32(0.85);
+                        cooldown.0.set_duration(new_duration);
+                    }
+                });
+            }
+            Upgrade::MoveSpeed => {
+                if let Some(mut upgrades) = world.get_mut::<PlayerUpgrades>(id) {
+                    upgrades.acceleration_multiplier *= 1.1;
+                }
+                if let Some(mut acceleration) = world.get_mut::<BaseAcceleration>(id) {
+                    acceleration.0 *= 1.1;
+                }
+            }
+            Upgrade::MaxHealth => {
+                if let Some(mut upgrades) = world.get_mut::<PlayerUpgrades>(id) {
+                    upgrades.bonus_health += 5.;
+                }
+                if let Some(mut health) = world.get_mut::<Health>(id) {
+                    health.max_hp += 5.;
+                    health.cur_hp += 5.;
+                }
+            }
+            Upgrade::PickupRadius => {
+                if let Some(mut upgrades) = world.get_mut::<PlayerUpgrades>(id) {
+                    upgrades.collection_radius_multiplier *= 1.25;
+                }
+                children_of(id, world).into_iter().for_each(|child| {
+                    let mut child_mut = world.entity_mut(child);
+                    if let Some(mut radius) = child_mut.get_mut::<CollectionRadius>() {
+                        radius.0 *= 1.25;
+                        let new_collider = Collider::circle(radius.0);
+                        child_mut.insert(new_collider);
+                    }
+                });
+            }
+            Upgrade::Recovery => Heal::<Player>::new(10.).apply(id, world),
+            Upgrade::Shotgun => EquipWeapon(Weapon::shotgun()).apply(id, world),
+            Upgrade::Nova => EquipWeapon(Weapon::nova()).apply(id, world),
+        }
+    }
+}
+
+/// Sent whenever the player picks an upgrade on level up.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct UpgradeChosen(pub Upgrade);
+
+/// Applies the picked upgrade to the player and resumes the run.
+pub struct ChooseUpgrade(pub Upgrade);
+
+impl Command for ChooseUpgrade {
+    fn apply(self, world: &mut World) {
+        let upgrade = self.0;
+        let player_entity = world
+            .query_filtered::<Entity, With<Player>>()
+            .get_single(world);
+        if let Ok(player_entity) = player_entity {
+            ApplyUpgrade(upgrade).apply(player_entity, world);
+        }
+        if upgrade.is_unique() {
+            world
+                .resource_mut::<UpgradePool>()
+                .0
+                .retain(|(pooled, _)| *pooled != upgrade);
+        }
+        world.send_event(UpgradeChosen(upgrade));
+        world
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Playing);
+    }
+}
+
+fn children_of(id: Entity, world: &World) -> Vec<Entity> {
+    world
+        .get::<Children>(id)
+        .map(|children| children.to_vec())
+        .unwrap_or_default()
+}
+
+fn held_weapons(id: Entity, world: &World) -> Vec<Entity> {
+    children_of(id, world)
+        .into_iter()
+        .filter(|child| world.get::<Weapon>(*child).is_some())
+        .collect()
+}
+
+fn offer_upgrades(
+    mut ev_reader: EventReader<LevelUpEvent>,
+    pool: Res<UpgradePool>,
+    mut choices: ResMut<UpgradeChoices>,
+    mut game_rng: ResMut<GameRng>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if let Some(level_up) = ev_reader.read().last() {
+        choices.level = level_up.new_level;
+    }
+
+    let count = choices.count;
+    choices.offered = pool.roll(count, game_rng.stream(RngStream::Upgrades));
+    if !choices.offered.is_empty() {
+        next_state.set(GameState::LevelUp);
+    }
+}
+
+const CHOICE_KEYS: [KeyCode; 5] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+];
+
+fn choose_upgrade(
+    keys: Res<ButtonInput<KeyCode>>,
+    choices: Res<UpgradeChoices>,
+    mut commands: Commands,
+) {
+    let Some(upgrade) = CHOICE_KEYS
+        .iter()
+        .zip(choices.offered.iter())
+        .find(|(key, _)| keys.just_pressed(**key))
+        .map(|(_, upgrade)| *upgrade)
+    else {
+        return;
+    };
+
+    commands.add(ChooseUpgrade(upgrade));
+}
+
+#[derive(Component)]
+struct LevelUpScreen;
+
+fn spawn_level_up_screen(mut commands: Commands, choices: Res<UpgradeChoices>) {
+    commands
+        .spawn((
+            NodeBundle {
+                background_color: Color::rgba(0., 0., 0., 0.6).into(),
+                ..screen_root()
+            },
+            LevelUpScreen,
+        ))
+        .with_children(|children| {
+            children.spawn(screen_text(format!("Level {}!", choices.level), 48.));
+            choices
+                .offered
+                .iter()
+                .enumerate()
+                .for_each(|(idx, upgrade)| {
+                    children.spawn(screen_text(
+                        format!("{} - {}", idx + 1, upgrade.description()),
+                        24.,
+                    ));
+                });
+        });
+}
@@ -0,0 +1,90 @@
+use std::{env, fmt, fs, io, path::PathBuf};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Settings the player changes from within the game, kept as a RON file in [`user_config_dir`].
+pub trait UserConfigFile: Serialize + DeserializeOwned {
+    /// Name of the file inside the user config directory.
+    const FILE_NAME: &'static str;
+
+    fn path() -> Option<PathBuf> {
+        user_config_dir().map(|dir| dir.join(Self::FILE_NAME))
+    }
+
+    fn load() -> Result<Self, UserConfigError> {
+        let path = Self::path().ok_or(UserConfigError::NoConfigDir)?;
+        let contents = fs::read_to_string(path)?;
+        Ok(ron::from_str(&contents)?)
+    }
+
+    fn save(&self) -> Result<PathBuf, UserConfigError> {
+        let path = Self::path().ok_or(UserConfigError::NoConfigDir)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let contents = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        fs::write(&path, contents)?;
+        Ok(path)
+    }
+}
+
+/// `cubes-attack` inside the platform's config directory.
+pub fn user_config_dir() -> Option<PathBuf> {
+    let home = || env::var_os("HOME").map(PathBuf::from);
+    let base = if cfg!(windows) {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        home().map(|home| home.join("Library").join("Application Support"))
+    } else {
+        env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| home().map(|home| home.join(".config")))
+    };
+    base.map(|dir| dir.join("cubes-attack"))
+}
+
+#[derive(Debug)]
+pub enum UserConfigError {
+    NoConfigDir,
+    Io(io::Error),
+    Parse(ron::error::SpannedError),
+    Serialize(ron::Error),
+}
+
+impl UserConfigError {
+    /// The file was never saved, so the defaults are the expected outcome.
+    pub fn is_missing_file(&self) -> bool {
+        matches!(self, UserConfigError::Io(err) if err.kind() == io::ErrorKind::NotFound)
+    }
+}
+
+impl fmt::Display for UserConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UserConfigError::NoConfigDir => write!(f, "could not find the user config directory"),
+            UserConfigError::Io(err) => write!(f, "could not access file: {err}"),
+            UserConfigError::Parse(err) => write!(f, "could not parse file: {err}"),
+            UserConfigError::Serialize(err) => write!(f, "could not serialize: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for UserConfigError {}
+
+impl From<io::Error> for UserConfigError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<ron::error::SpannedError> for UserConfigError {
+    fn from(err: ron::error::SpannedError) -> Self {
+        Self::Parse(err)
+    }
+}
+
+impl From<ron::Error> for UserConfigError {
+    fn from(err: ron::Error) -> Self {
+        Self::Serialize(err)
+    }
+}
@@ -0,0 +1,291 @@
+use bevy::{ecs::system::EntityCommand, prelude::*};
+use bevy_xpbd_2d::prelude::*;
+use leafwing_input_manager::prelude::*;
+use rand::Rng;
+
+use crate::{
+    bullet::{
+        CursorPosition, ProjectileDamage, ShootCooldown, ShootEvent, ShotDirection, ShotLocation,
+    },
+    enemy::AttackRange,
+    prelude::*,
+    rng::{GameRng, RngStream},
+    status_effects::StatusEffect,
+};
+
+/// Damage multiplier of a critical hit.
+pub const CRIT_MULTIPLIER: f32 = 2.;
+
+/// How the projectiles of a single volley are spread around the aim direction.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SpreadPattern {
+    /// Each projectile is randomly deflected by up to `angle` radians either way.
+    Random { angle: f32 },
+    /// Projectiles are evenly fanned out across `angle` radians centered on the aim direction.
+    Fan { angle: f32 },
+    /// Projectiles are evenly distributed around the shooter, starting at the aim direction.
+    Radial,
+}
+
+impl SpreadPattern {
+    pub fn directions(&self, aim: Vec2, count: u32, rng: &mut impl Rng) -> Vec<Vec2> {
+        let rotate = |angle: f32| {
+            Quat::from_rotation_z(angle)
+                .mul_vec3(aim.extend(0.))
+                .truncate()
+                .normalize_or_zero()
+        };
+
+        (0..count)
+            .map(|idx| match *self {
+                SpreadPattern::Random { angle } if angle > 0. => {
+                    rotate(rng.gen_range(-angle..angle))
+                }
+                SpreadPattern::Random { .. } => rotate(0.),
+                SpreadPattern::Fan { angle } if count > 1 => {
+                    rotate(-angle / 2. + angle * idx as f32 / (count - 1) as f32)
+                }
+                SpreadPattern::Fan { .. } => rotate(0.),
+                SpreadPattern::Radial => rotate(std::f32::consts::TAU * idx as f32 / count as f32),
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ProjectileSprite {
+    pub color: Color,
+    pub size: f32,
+}
+
+/// Describes a weapon. Weapons live on child entities of their holder and fire independently.
+#[derive(Component, Debug, Clone)]
+pub struct Weapon {
+    pub projectile_count: u32,
+    pub spread: SpreadPattern,
+    /// Passed to the projectile's [`MovementAcceleration`].
+    pub speed: f32,
+    pub damage: f32,
+    /// Velocity change given to what the projectiles hit.
+    pub knockback: f32,
+    /// Seconds between volleys.
+    pub cooldown: f32,
+    /// Seconds before a projectile expires.
+    pub lifetime: f32,
+    pub sprite: ProjectileSprite,
+    /// Status effect applied to whatever the projectiles hit.
+    pub on_hit: Option<StatusEffect>,
+    /// Chance for a projectile to deal [`CRIT_MULTIPLIER`] times its damage, rolled on every hit.
+    pub crit_chance: f32,
+}
+
+impl Weapon {
+    pub fn pistol() -> Self {
+        Self {
+            projectile_count: 1,
+            spread: SpreadPattern::Random {
+                angle: std::f32::consts::PI / 36.,
+            },
+            speed: 30000.,
+            damage: 5.,
+            knockback: 40.,
+            cooldown: 0.25,
+            lifetime: 4.,
+            sprite: ProjectileSprite {
+                color: Color::YELLOW,
+                size: 4.,
+            },
+            on_hit: None,
+            crit_chance: 0.1,
+        }
+    }
+
+    pub fn shotgun() -> Self {
+        Self {
+            projectile_count: 5,
+            spread: SpreadPattern::Fan {
+                angle: std::f32::consts::PI / 4.,
+            },
+            speed: 24000.,
+            damage: 3.,
+            knockback: 30.,
+            cooldown: 1.,
+            lifetime: 0.6,
+            sprite: ProjectileSprite {
+                color: Color::ORANGE,
+                size: 4.,
+            },
+            on_hit: None,
+            crit_chance: 0.05,
+        }
+    }
+
+    pub fn nova() -> Self {
+        Self {
+            projectile_count: 12,
+            spread: SpreadPattern::Radial,
+            speed: 18000.,
+            damage: 4.,
+            knockback: 60.,
+            cooldown: 2.,
+            lifetime: 1.5,
+            sprite: ProjectileSprite {
+                color: Color::CYAN,
+                size: 6.,
+            },
+            on_hit: None,
+            crit_chance: 0.,
+        }
+    }
+
+    pub fn enemy_bolt() -> Self {
+        Self {
+            projectile_count: 1,
+            spread: SpreadPattern::Random {
+                angle: std::f32::consts::PI / 24.,
+            },
+            speed: 12000.,
+            damage: 3.,
+            knockback: 80.,
+            cooldown: 2.,
+            lifetime: 3.,
+            sprite: ProjectileSprite {
+                color: Color::FUCHSIA,
+                size: 6.,
+            },
+            on_hit: Some(StatusEffect::slow()),
+            crit_chance: 0.,
+        }
+    }
+
+    pub fn with_on_hit(mut self, effect: StatusEffect) -> Self {
+        self.on_hit = Some(effect);
+        self
+    }
+}
+
+#[derive(Bundle)]
+pub struct WeaponBundle {
+    weapon: Weapon,
+    damage: ProjectileDamage,
+    cooldown: ShootCooldown,
+}
+
+impl WeaponBundle {
+    pub fn new(weapon: Weapon) -> Self {
+        Self {
+            damage: ProjectileDamage(weapon.damage),
+            cooldown: ShootCooldown::ready(weapon.cooldown),
+            weapon,
+        }
+    }
+}
+
+/// Gives the entity another weapon, which fires alongside the ones it already holds.
+pub struct EquipWeapon(pub Weapon);
+
+impl EntityCommand for EquipWeapon {
+    fn apply(self, id: Entity, world: &mut World) {
+        let weapon = world.spawn(WeaponBundle::new(self.0)).id();
+        world.entity_mut(id).add_child(weapon);
+    }
+}
+
+fn fire_volley(
+    weapon: &Weapon,
+    damage: ProjectileDamage,
+    shot_location: Vec2,
+    aim: Vec2,
+    collision_layers: CollisionLayers,
+    rng: &mut impl Rng,
+    ev_writer: &mut EventWriter<ShootEvent>,
+) {
+    weapon
+        .spread
+        .directions(aim, weapon.projectile_count, rng)
+        .into_iter()
+        .for_each(|direction| {
+            ev_writer.send(ShootEvent {
+                shot_location: ShotLocation(shot_location),
+                shot_direction: ShotDirection(direction),
+                collision_layers,
+                acceleration: MovementAcceleration(weapon.speed),
+                damage,
+                knockback: weapon.knockback,
+                lifetime: weapon.lifetime,
+                sprite: weapon.sprite,
+                on_hit: weapon.on_hit,
+                crit_chance: weapon.crit_chance,
+            });
+        });
+}
+
+pub(crate) fn fire_weapons(
+    q_player: Query<(&ActionState<Action>, &Transform, Entity), With<Player>>,
+    mut q_weapons: Query<(&Weapon, &ProjectileDamage, &mut ShootCooldown, &Parent)>,
+    cursor_position: Res<CursorPosition>,
+    mut game_rng: ResMut<GameRng>,
+    mut ev_writer: EventWriter<ShootEvent>,
+) {
+    let Ok((action_state, transform, player_entity)) = q_player.get_single() else {
+        return;
+    };
+    if !action_state.pressed(&Action::Shoot) {
+        return;
+    }
+
+    let shot_location = transform.translation.truncate();
+    let aim = cursor_position.0 - shot_location;
+
+    q_weapons
+        .iter_mut()
+        .filter(|(.., parent)| parent.get() == player_entity)
+        .filter(|(_, _, cooldown, _)| cooldown.0.finished())
+        .for_each(|(weapon, damage, mut cooldown, _)| {
+            fire_volley(
+                weapon,
+                *damage,
+                shot_location,
+                aim,
+                CollisionLayers::new(GameLayer::Bullet, [GameLayer::Enemy]),
+                game_rng.stream(RngStream::PlayerWeapons),
+                &mut ev_writer,
+            );
+            cooldown.0.reset();
+        });
+}
+
+/// Fires the weapons of enemies that have the player within their [`AttackRange`].
+pub(crate) fn fire_enemy_weapons(
+    q_enemies: Query<(&Transform, &AttackRange), With<Enemy>>,
+    mut q_weapons: Query<(&Weapon, &ProjectileDamage, &mut ShootCooldown, &Parent)>,
+    player_pos: Res<PlayerPosition>,
+    mut game_rng: ResMut<GameRng>,
+    mut ev_writer: EventWriter<ShootEvent>,
+) {
+    q_weapons
+        .iter_mut()
+        .filter(|(_, _, cooldown, _)| cooldown.0.finished())
+        .for_each(|(weapon, damage, mut cooldown, parent)| {
+            let Ok((transform, attack_range)) = q_enemies.get(parent.get()) else {
+                return;
+            };
+
+            let shot_location = transform.translation.truncate();
+            let aim = player_pos.0 - shot_location;
+            if aim.length() > attack_range.0 {
+                return;
+            }
+
+            fire_volley(
+                weapon,
+                *damage,
+                shot_location,
+                aim,
+                CollisionLayers::new(GameLayer::EnemyBullet, [GameLayer::Player]),
+                game_rng.stream(RngStream::EnemyWeapons),
+                &mut ev_writer,
+            );
+            cooldown.0.reset();
+        });
+}
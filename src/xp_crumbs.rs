@@ -3,19 +3,68 @@ use std::marker::PhantomData;
 use bevy::{ecs::system::Command, prelude::*};
 use bevy_xpbd_2d::prelude::*;
 
-use crate::{hurtbox::Heal, prelude::GameLayer, Player};
+use crate::{
+    config::{ConfigReloaded, ConfigSyncSet, CrumbConfig},
+    game_state::GameState,
+    hurtbox::Heal,
+    player::CollectionRadius,
+    prelude::{GameLayer, PlayerPosition},
+    stats::RunStats,
+    Player,
+};
+
+/// Speed a crumb starts moving at once it is magnetized.
+const ATTRACTION_START_SPEED: f32 = 150.;
+/// Speed gained per second while being pulled in.
+const ATTRACTION_ACCELERATION: f32 = 1500.;
+/// Keeps fast crumbs from stepping over the player's body in a single tick.
+const MAX_ATTRACTION_SPEED: f32 = 1200.;
 
 pub struct XpCrumbPlugin;
 
 impl Plugin for XpCrumbPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(PlayerLevel::default()).add_systems(
-            Update,
-            (
-                (collect_xp_system, update_level_system).chain(),
-                collect_healing_system,
-            ),
-        );
+        app.insert_resource(PlayerLevel::default())
+            .add_event::<LevelUpEvent>()
+            .add_systems(
+                FixedUpdate,
+                (
+                    magnetize_crumbs,
+                    attract_crumbs,
+                    (
+                        (collect_xp_system, update_level_system).chain(),
+                        collect_healing_system,
+                        collect_magnet_system,
+                    ),
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                Update,
+                apply_crumb_config
+                    .after(ConfigSyncSet)
+                    .run_if(on_event::<ConfigReloaded<CrumbConfig>>()),
+            );
+    }
+}
+
+/// Any pickup lying on the map. Crumbs are pulled in once they are [`Magnetized`] and
+/// collected when they touch the player's body.
+#[derive(Component)]
+pub struct Crumb;
+
+/// A crumb being pulled towards the player, faster and faster.
+#[derive(Component)]
+pub struct Magnetized {
+    speed: f32,
+}
+
+impl Default for Magnetized {
+    fn default() -> Self {
+        Self {
+            speed: ATTRACTION_START_SPEED,
+        }
     }
 }
 
@@ -27,6 +76,7 @@ pub struct XpValue(f32);
 
 #[derive(Bundle)]
 pub struct XpCrumbBundle {
+    crumb: Crumb,
     xp_crumb: XpCrumb,
     rigid_body: RigidBody,
     collider: Collider,
@@ -38,8 +88,9 @@ pub struct XpCrumbBundle {
 impl XpCrumbBundle {
     pub fn new(value: f32) -> Self {
         Self {
+            crumb: Crumb,
             xp_crumb: XpCrumb,
-            rigid_body: RigidBody::Static,
+            rigid_body: RigidBody::Kinematic,
             collider: Collider::rectangle(5., 5.),
             sensor: Sensor,
             collision_layers: CollisionLayers::new(GameLayer::XpCrumb, [GameLayer::Player]),
@@ -56,6 +107,7 @@ pub struct HealAmount(pub f32);
 
 #[derive(Bundle)]
 pub struct HealingCrumbBundle {
+    crumb: Crumb,
     healing_crumb: HealingCrumb,
     rigid_body: RigidBody,
     collider: Collider,
@@ -67,8 +119,9 @@ pub struct HealingCrumbBundle {
 impl HealingCrumbBundle {
     pub fn new(heal_amount: f32) -> Self {
         Self {
+            crumb: Crumb,
             healing_crumb: HealingCrumb,
-            rigid_body: RigidBody::Static,
+            rigid_body: RigidBody::Kinematic,
             collider: Collider::rectangle(10., 10.),
             sensor: Sensor,
             collision_layers: CollisionLayers::new(GameLayer::HealingCrumb, [GameLayer::Player]),
@@ -77,6 +130,48 @@ impl HealingCrumbBundle {
     }
 }
 
+/// Magnetizes every crumb on the map when collected.
+#[derive(Component)]
+pub struct MagnetCrumb;
+
+#[derive(Bundle)]
+pub struct MagnetCrumbBundle {
+    crumb: Crumb,
+    magnet_crumb: MagnetCrumb,
+    rigid_body: RigidBody,
+    collider: Collider,
+    sensor: Sensor,
+    collision_layers: CollisionLayers,
+}
+
+impl Default for MagnetCrumbBundle {
+    fn default() -> Self {
+        Self {
+            crumb: Crumb,
+            magnet_crumb: MagnetCrumb,
+            rigid_body: RigidBody::Kinematic,
+            collider: Collider::circle(6.),
+            sensor: Sensor,
+            collision_layers: CollisionLayers::new(GameLayer::MagnetCrumb, [GameLayer::Player]),
+        }
+    }
+}
+
+/// Applies an edited crumb config to the crumbs that are lying around.
+fn apply_crumb_config(
+    mut ev_reader: EventReader<ConfigReloaded<CrumbConfig>>,
+    config: Res<CrumbConfig>,
+    mut q_xp: Query<&mut XpValue>,
+    mut q_healing: Query<&mut HealAmount>,
+) {
+    ev_reader.clear();
+    q_xp.iter_mut()
+        .for_each(|mut xp_value| xp_value.0 = config.xp_value);
+    q_healing
+        .iter_mut()
+        .for_each(|mut heal_amount| heal_amount.0 = config.heal_amount);
+}
+
 pub struct SpawnCrumb<T: Component> {
     pub position: Vec2,
     marker: PhantomData<T>,
@@ -93,6 +188,7 @@ impl<T: Component> SpawnCrumb<T> {
 
 impl Command for SpawnCrumb<XpCrumb> {
     fn apply(self, world: &mut World) {
+        let xp_value = world.resource::<CrumbConfig>().xp_value;
         world.spawn((
             SpriteBundle {
                 sprite: Sprite {
@@ -103,13 +199,14 @@ impl Command for SpawnCrumb<XpCrumb> {
                 transform: Transform::from_translation(self.position.extend(0.)),
                 ..Default::default()
             },
-            XpCrumbBundle::new(5.),
+            XpCrumbBundle::new(xp_value),
         ));
     }
 }
 
 impl Command for SpawnCrumb<HealingCrumb> {
     fn apply(self, world: &mut World) {
+        let heal_amount = world.resource::<CrumbConfig>().heal_amount;
         world.spawn((
             SpriteBundle {
                 sprite: Sprite {
@@ -121,16 +218,33 @@ impl Command for SpawnCrumb<HealingCrumb> {
                     .with_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_4)),
                 ..Default::default()
             },
-            HealingCrumbBundle::new(5.),
+            HealingCrumbBundle::new(heal_amount),
+        ));
+    }
+}
+
+impl Command for SpawnCrumb<MagnetCrumb> {
+    fn apply(self, world: &mut World) {
+        world.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    custom_size: Some(Vec2::splat(12.)),
+                    color: Color::rgb(0.3, 0.5, 1.),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(self.position.extend(0.)),
+                ..Default::default()
+            },
+            MagnetCrumbBundle::default(),
         ));
     }
 }
 
 #[derive(Resource)]
 pub struct PlayerLevel {
-    cur_level: u32,
-    cur_xp: f32,
-    next_level_threshold: f32,
+    pub cur_level: u32,
+    pub cur_xp: f32,
+    pub next_level_threshold: f32,
 }
 
 impl Default for PlayerLevel {
@@ -143,7 +257,15 @@ impl Default for PlayerLevel {
     }
 }
 
-fn update_level_system(mut player_level: ResMut<PlayerLevel>) {
+#[derive(Event, Debug)]
+pub struct LevelUpEvent {
+    pub new_level: u32,
+}
+
+fn update_level_system(
+    mut player_level: ResMut<PlayerLevel>,
+    mut ev_writer: EventWriter<LevelUpEvent>,
+) {
     let PlayerLevel {
         cur_xp,
         next_level_threshold,
@@ -154,41 +276,108 @@ fn update_level_system(mut player_level: ResMut<PlayerLevel>) {
         player_level.cur_level += 1;
         player_level.cur_xp = 0.;
         player_level.next_level_threshold *= 1.5;
+        ev_writer.send(LevelUpEvent {
+            new_level: player_level.cur_level,
+        });
     }
 }
 
+/// Crumbs that enter the player's collection radius start flying towards them.
+fn magnetize_crumbs(
+    q_radius: Query<&CollidingEntities, With<CollectionRadius>>,
+    q_crumbs: Query<(), (With<Crumb>, Without<Magnetized>)>,
+    mut commands: Commands,
+) {
+    q_radius
+        .iter()
+        .flat_map(|colliding_entities| colliding_entities.0.iter())
+        .filter(|entity| q_crumbs.contains(**entity))
+        .for_each(|entity| {
+            commands.entity(*entity).insert(Magnetized::default());
+        });
+}
+
+fn attract_crumbs(
+    time: Res<Time>,
+    player_pos: Res<PlayerPosition>,
+    mut q_magnetized: Query<(&Transform, &mut Magnetized, &mut LinearVelocity)>,
+) {
+    q_magnetized
+        .iter_mut()
+        .for_each(|(transform, mut magnetized, mut velocity)| {
+            magnetized.speed = (magnetized.speed + ATTRACTION_ACCELERATION * time.delta_seconds())
+                .min(MAX_ATTRACTION_SPEED);
+            let to_player = player_pos.0 - transform.translation.truncate();
+            velocity.0 = to_player.normalize_or_zero() * magnetized.speed;
+        });
+}
+
+/// Whether the crumb touches the player's body, rather than just their collection radius.
+fn touches_player(colliding_entities: &CollidingEntities, player: Entity) -> bool {
+    colliding_entities.0.contains(&player)
+}
+
 fn collect_xp_system(
     q_xp_collisions: Query<(Entity, &XpValue, &CollidingEntities), With<XpCrumb>>,
+    q_player: Query<Entity, With<Player>>,
     mut player_level: ResMut<PlayerLevel>,
+    mut run_stats: ResMut<RunStats>,
     mut commands: Commands,
 ) {
+    let Ok(player) = q_player.get_single() else {
+        return;
+    };
     q_xp_collisions
         .iter()
-        .for_each(|(entity, xp_value, colliding_entities)| {
-            if !colliding_entities.0.is_empty() {
-                player_level.cur_xp += xp_value.0;
-                commands.entity(entity).despawn_recursive();
-            }
+        .filter(|(.., colliding_entities)| touches_player(colliding_entities, player))
+        .for_each(|(entity, xp_value, _)| {
+            player_level.cur_xp += xp_value.0;
+            run_stats.xp_collected += xp_value.0;
+            commands.entity(entity).despawn_recursive();
         })
 }
 
 fn collect_healing_system(
     q_healing_collisions: Query<(Entity, &HealAmount, &CollidingEntities), With<HealingCrumb>>,
+    q_player: Query<Entity, With<Player>>,
     mut commands: Commands,
 ) {
+    let Ok(player) = q_player.get_single() else {
+        return;
+    };
     q_healing_collisions
         .iter()
-        .for_each(|(entity, heal_amount, colliding_entities)| {
-            if !colliding_entities.0.is_empty() {
-                let player_entity = colliding_entities
-                    .0
-                    .iter()
-                    .last()
-                    .expect("Player is somehow missing from array");
-                commands
-                    .entity(*player_entity)
-                    .add(Heal::<Player>::new(heal_amount.0));
-                commands.entity(entity).despawn_recursive();
-            }
+        .filter(|(.., colliding_entities)| touches_player(colliding_entities, player))
+        .for_each(|(entity, heal_amount, _)| {
+            commands
+                .entity(player)
+                .add(Heal::<Player>::new(heal_amount.0));
+            commands.entity(entity).despawn_recursive();
         })
 }
+
+#[allow(clippy::type_complexity)]
+fn collect_magnet_system(
+    q_magnet_collisions: Query<(Entity, &CollidingEntities), With<MagnetCrumb>>,
+    q_player: Query<Entity, With<Player>>,
+    q_crumbs: Query<Entity, (With<Crumb>, Without<MagnetCrumb>, Without<Magnetized>)>,
+    mut commands: Commands,
+) {
+    let Ok(player) = q_player.get_single() else {
+        return;
+    };
+    let mut collected = false;
+    q_magnet_collisions
+        .iter()
+        .filter(|(_, colliding_entities)| touches_player(colliding_entities, player))
+        .for_each(|(entity, _)| {
+            commands.entity(entity).despawn_recursive();
+            collected = true;
+        });
+
+    if collected {
+        q_crumbs.iter().for_each(|crumb| {
+            commands.entity(crumb).insert(Magnetized::default());
+        });
+    }
+}
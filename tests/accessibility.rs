@@ -0,0 +1,63 @@
+mod common;
+
+use bevy::{ecs::system::EntityCommand, prelude::*};
+use bevy_physics_playground::{
+    accessibility::{AccessibilitySettings, AutoAim},
+    bullet::CursorPosition,
+    enemy::EnemyKind,
+    hurtbox::TakeDamage,
+    prelude::*,
+};
+use common::{headless_app, spawn_enemy, step};
+
+fn aim_with(app: &mut App, auto_aim: AutoAim) -> Vec2 {
+    app.insert_resource(AccessibilitySettings {
+        auto_fire: false,
+        auto_aim,
+    });
+    step(app, 1);
+    app.world.resource::<CursorPosition>().0
+}
+
+fn position(app: &App, entity: Entity) -> Vec2 {
+    app.world
+        .get::<Transform>(entity)
+        .expect("Entity does not have a transform")
+        .translation
+        .truncate()
+}
+
+#[test]
+fn auto_aim_picks_its_target_within_range() {
+    let mut app = headless_app();
+    app.add_plugins(AccessibilityPlugin);
+    let player_pos = app.world.resource::<PlayerPosition>().0;
+
+    let nearest = spawn_enemy(&mut app, player_pos + Vec2::new(200., 0.), EnemyKind::Melee);
+    let wounded = spawn_enemy(&mut app, player_pos + Vec2::new(300., 0.), EnemyKind::Melee);
+    let wounded_far = spawn_enemy(
+        &mut app,
+        player_pos + Vec2::new(-450., 0.),
+        EnemyKind::Melee,
+    );
+    let out_of_range = spawn_enemy(&mut app, player_pos + Vec2::new(0., 900.), EnemyKind::Melee);
+    TakeDamage::<Enemy>::new(5.).apply(wounded, &mut app.world);
+    TakeDamage::<Enemy>::new(5.).apply(wounded_far, &mut app.world);
+    TakeDamage::<Enemy>::new(10.).apply(out_of_range, &mut app.world);
+
+    let cursor = aim_with(&mut app, AutoAim::Nearest);
+    assert_eq!(cursor, position(&app, nearest));
+
+    // Equally wounded enemies are told apart by their distance
+    let cursor = aim_with(&mut app, AutoAim::LowestHealth);
+    assert_eq!(cursor, position(&app, wounded));
+
+    [nearest, wounded, wounded_far]
+        .into_iter()
+        .for_each(|enemy| {
+            app.world.despawn(enemy);
+        });
+    let cursor = aim_with(&mut app, AutoAim::Nearest);
+    let player_pos = app.world.resource::<PlayerPosition>().0;
+    assert_eq!(cursor, player_pos);
+}
@@ -0,0 +1,42 @@
+use bevy::prelude::*;
+use bevy_physics_playground::bindings::{Binding, KeyBindings};
+use leafwing_input_manager::user_input::InputKind;
+
+#[test]
+fn rebinding_to_a_used_input_is_refused() {
+    let mut bindings = KeyBindings::default();
+    let dash = bindings.get(Binding::Dash);
+
+    assert_eq!(
+        bindings.conflict(Binding::MoveUp, dash),
+        Some(Binding::Dash)
+    );
+    assert_eq!(bindings.rebind(Binding::MoveUp, dash), Err(Binding::Dash));
+    assert_eq!(bindings, KeyBindings::default());
+}
+
+#[test]
+fn rebinding_a_slot_to_its_own_input_is_allowed() {
+    let mut bindings = KeyBindings::default();
+    let shoot = bindings.get(Binding::Shoot);
+
+    assert_eq!(bindings.conflict(Binding::Shoot, shoot), None);
+    assert_eq!(bindings.rebind(Binding::Shoot, shoot), Ok(()));
+
+    let free = InputKind::PhysicalKey(KeyCode::Space);
+    assert_eq!(bindings.rebind(Binding::Dash, free), Ok(()));
+    assert_eq!(bindings.get(Binding::Dash), free);
+}
+
+#[test]
+fn bindings_survive_a_ron_round_trip() {
+    let mut bindings = KeyBindings::default();
+    bindings.set(Binding::Shoot, InputKind::PhysicalKey(KeyCode::KeyJ));
+    bindings.set(Binding::Dash, InputKind::Mouse(MouseButton::Right));
+
+    let ron = ron::ser::to_string_pretty(&bindings, ron::ser::PrettyConfig::default())
+        .expect("Failed to serialize bindings");
+    let loaded: KeyBindings = ron::from_str(&ron).expect("Failed to parse bindings");
+
+    assert_eq!(loaded, bindings);
+}
@@ -0,0 +1,131 @@
+mod common;
+
+use std::{
+    io::{Cursor, Write},
+    sync::{Arc, Mutex},
+};
+
+use bevy::prelude::*;
+use bevy_physics_playground::{
+    bot::{
+        BotCommand, BotConnection, BotMessage, EnemyObservation, Observation, PlayerObservation,
+    },
+    enemy::EnemyKind,
+    headless::ScriptedInput,
+    prelude::*,
+};
+use common::{headless_app, spawn_enemy, step};
+use serde_json::{json, Value};
+
+/// Collects everything the game sends, so the test can read it back.
+#[derive(Clone, Default)]
+struct SentLines(Arc<Mutex<Vec<u8>>>);
+
+impl Write for SentLines {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().expect("Poisoned lock").write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl SentLines {
+    fn messages(&self) -> Vec<Value> {
+        let sent = self.0.lock().expect("Poisoned lock");
+        String::from_utf8_lossy(&sent)
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("Sent line is not JSON"))
+            .collect()
+    }
+}
+
+fn command(json: Value) -> ScriptedInput {
+    serde_json::from_value::<BotCommand>(json)
+        .expect("Failed to parse command")
+        .into()
+}
+
+#[test]
+fn observations_serialize_as_tagged_json() {
+    let observation = Observation {
+        tick: 3,
+        player: PlayerObservation {
+            position: Vec2::new(1., 2.),
+            velocity: Vec2::ZERO,
+            hp: 10.,
+            max_hp: 15.,
+        },
+        level: 2,
+        xp: 4.,
+        next_level_threshold: 8.,
+        enemies: vec![EnemyObservation {
+            position: Vec2::new(-5., 0.),
+            velocity: Vec2::X,
+            hp: 3.,
+            ranged: true,
+        }],
+        crumbs: Vec::new(),
+    };
+
+    let value = serde_json::to_value(BotMessage::Observation(observation))
+        .expect("Failed to serialize observation");
+    assert_eq!(value["type"], "observation");
+    assert_eq!(value["tick"], 3);
+    assert_eq!(value["player"]["position"], json!([1., 2.]));
+    assert_eq!(value["player"]["max_hp"], 15.);
+    assert_eq!(value["enemies"][0]["ranged"], true);
+    assert_eq!(value["crumbs"], json!([]));
+}
+
+#[test]
+fn commands_map_to_scripted_input() {
+    let input = command(json!({
+        "actions": ["Move", "Shoot"],
+        "movement": [0., 1.],
+        "aim": [1., 0.],
+        "upgrade": 2,
+    }));
+    assert_eq!(input.movement, Vec2::Y);
+    assert!(input.shoot);
+    assert!(!input.dash);
+    assert_eq!(input.aim, Vec2::X);
+    assert_eq!(input.upgrade, 2);
+}
+
+#[test]
+fn movement_needs_the_move_action() {
+    let input = command(json!({ "actions": ["Dash"], "movement": [1., 0.] }));
+    assert_eq!(input.movement, Vec2::ZERO);
+    assert!(input.dash);
+
+    let idle = command(json!({}));
+    assert_eq!(idle.movement, Vec2::ZERO);
+    assert!(!idle.shoot);
+}
+
+#[test]
+fn a_tick_is_exchanged_for_a_command() {
+    let mut app = headless_app();
+    app.add_plugins(BotPlugin);
+    spawn_enemy(&mut app, Vec2::new(300., 0.), EnemyKind::Ranged);
+    // Enemies are only observed once physics gave them a velocity
+    step(&mut app, 1);
+
+    let sent = SentLines::default();
+    let commands = "not json\n{\"actions\": [\"Shoot\"], \"aim\": [0, 1]}\n";
+    app.insert_resource(BotConnection::new(Cursor::new(commands), sent.clone()));
+    step(&mut app, 1);
+
+    let messages = sent.messages();
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[0]["type"], "observation");
+    assert_eq!(messages[0]["tick"], 0);
+    assert_eq!(messages[0]["enemies"][0]["ranged"], true);
+    assert_eq!(messages[1]["type"], "error");
+
+    let script = app.world.resource::<ScriptedInput>();
+    assert!(script.shoot);
+    assert_eq!(script.aim, Vec2::Y);
+}
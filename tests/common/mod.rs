@@ -0,0 +1,98 @@
+// Each test file uses its own subset of these helpers
+#![allow(dead_code)]
+
+use bevy::{ecs::system::Command, prelude::*};
+use bevy_physics_playground::{
+    director::SpawnDirector,
+    enemy::{EnemyKind, SpawnEnemy},
+    game_state::GameState,
+    headless::ScriptedInput,
+    prelude::*,
+    rng::RunSeed,
+    weapon::Weapon,
+};
+
+/// A windowless app that is already in a run, with only the enemies the test spawns itself.
+pub fn headless_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, HeadlessPlugin, SimulationPlugin))
+        .insert_resource(RunSeed(Some(0)));
+
+    for _ in 0..10 {
+        if *app.world.resource::<State<GameState>>().get() == GameState::Playing {
+            // Only the enemies the test spawns itself, the opening wave only starts after a second
+            app.insert_resource(SpawnDirector::new(Vec::new()));
+            return app;
+        }
+        app.update();
+    }
+    panic!("Headless app never started a run");
+}
+
+/// Advances the simulation by `ticks` fixed steps.
+pub fn step(app: &mut App, ticks: u32) {
+    (0..ticks).for_each(|_| app.update());
+}
+
+pub fn player(app: &mut App) -> Entity {
+    app.world
+        .query_filtered::<Entity, With<Player>>()
+        .single(&app.world)
+}
+
+pub fn spawn_enemy(app: &mut App, position: Vec2, kind: EnemyKind) -> Entity {
+    let existing = enemies(app);
+    SpawnEnemy::new(position, kind).apply(&mut app.world);
+
+    let spawned = enemies(app)
+        .into_iter()
+        .filter(|enemy| !existing.contains(enemy))
+        .collect::<Vec<_>>();
+    assert_eq!(spawned.len(), 1, "Expected exactly one new enemy");
+    spawned[0]
+}
+
+fn enemies(app: &mut App) -> Vec<Entity> {
+    app.world
+        .query_filtered::<Entity, With<Enemy>>()
+        .iter(&app.world)
+        .collect()
+}
+
+pub fn health(app: &App, entity: Entity) -> Health {
+    *app.world
+        .get::<Health>(entity)
+        .expect("Entity does not have health")
+}
+
+pub fn count<T: Component>(app: &mut App) -> usize {
+    app.world
+        .query_filtered::<(), With<T>>()
+        .iter(&app.world)
+        .count()
+}
+
+/// Changes every weapon the player holds.
+pub fn edit_player_weapon(app: &mut App, edit: impl Fn(&mut Weapon)) {
+    let player = player(app);
+    app.world
+        .query::<(&mut Weapon, &Parent)>()
+        .iter_mut(&mut app.world)
+        .filter(|(_, parent)| parent.get() == player)
+        .for_each(|(mut weapon, _)| edit(&mut weapon));
+}
+
+/// Shoots to the right, stopping at the first tick `enemy` loses health. Returns whether it got
+/// hit within a second.
+pub fn shoot_until_hit(app: &mut App, enemy: Entity) -> bool {
+    let hp = health(app, enemy).cur_hp;
+    *app.world.resource_mut::<ScriptedInput>() = ScriptedInput {
+        shoot: true,
+        aim: Vec2::X,
+        ..Default::default()
+    };
+    (0..60).any(|_| {
+        step(app, 1);
+        health(app, enemy).cur_hp < hp
+    })
+}
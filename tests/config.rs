@@ -0,0 +1,76 @@
+use std::path::Path;
+
+use bevy_physics_playground::config::{
+    ConfigAsset, ConfigLoadError, CrumbConfig, EnemyConfig, MovementConfig, PlayerConfig,
+};
+
+#[test]
+fn shipped_configs_are_valid() {
+    let assets = Path::new(env!("CARGO_MANIFEST_DIR")).join("assets");
+
+    PlayerConfig::read_file(&assets).expect("Player config is invalid");
+    EnemyConfig::read_file(&assets).expect("Enemy config is invalid");
+    CrumbConfig::read_file(&assets).expect("Crumb config is invalid");
+}
+
+#[test]
+fn defaults_are_valid() {
+    PlayerConfig::default().validate().unwrap();
+    EnemyConfig::default().validate().unwrap();
+    CrumbConfig::default().validate().unwrap();
+}
+
+#[test]
+fn out_of_range_values_are_all_reported() {
+    let default = EnemyConfig::default();
+    let config = EnemyConfig {
+        health: 0.,
+        size: -32.,
+        movement: MovementConfig {
+            acceleration: f32::NAN,
+            ..default.movement
+        },
+        ..default
+    };
+
+    let Err(ConfigLoadError::Invalid(problems)) = config.validate() else {
+        panic!("Invalid enemy config was accepted");
+    };
+    assert_eq!(problems.len(), 3);
+}
+
+#[test]
+fn drop_chances_must_be_within_zero_and_one() {
+    let config = CrumbConfig {
+        healing_drop_chance: 1.5,
+        ..Default::default()
+    };
+
+    assert!(config.validate().is_err());
+}
+
+#[test]
+fn drop_chances_must_add_up() {
+    let config = CrumbConfig {
+        healing_drop_chance: 0.7,
+        magnet_drop_chance: 0.5,
+        ..Default::default()
+    };
+
+    assert!(config.validate().is_err());
+}
+
+#[test]
+fn parsing_rejects_invalid_values() {
+    let ron = br#"(
+        size: 32.0,
+        health: 0.5,
+        collection_radius: 0.0,
+        movement: (acceleration: 1250.0, damping: 6.32),
+    )"#;
+
+    assert!(matches!(
+        PlayerConfig::parse(ron),
+        Err(ConfigLoadError::Invalid(problems)) if problems.len() == 1
+    ));
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use bevy::{
+    ecs::system::{Command, EntityCommand},
+    prelude::*,
+};
+use bevy_physics_playground::{
+    enemy::EnemyKind,
+    game_state::EndRun,
+    hurtbox::TakeDamage,
+    prelude::*,
+    xp_crumbs::{Crumb, HealingCrumb, MagnetCrumb, Magnetized, SpawnCrumb, XpCrumb},
+};
+use common::{count, headless_app, health, player, spawn_enemy, step};
+
+fn crumb_positions<T: Component>(app: &mut App) -> Vec<Vec2> {
+    app.world
+        .query_filtered::<&Transform, With<T>>()
+        .iter(&app.world)
+        .map(|transform| transform.translation.truncate())
+        .collect()
+}
+
+#[test]
+fn crumbs_in_the_radius_are_pulled_in() {
+    let mut app = headless_app();
+    SpawnCrumb::<XpCrumb>::new(Vec2::new(150., 0.)).apply(&mut app.world);
+
+    step(&mut app, 5);
+    let positions = crumb_positions::<XpCrumb>(&mut app);
+    assert_eq!(positions.len(), 1, "Crumb was collected from afar");
+    assert!(positions[0].x < 150.);
+
+    step(&mut app, 60);
+    assert_eq!(count::<XpCrumb>(&mut app), 0);
+}
+
+#[test]
+fn crumbs_outside_the_radius_stay_put() {
+    let mut app = headless_app();
+    SpawnCrumb::<XpCrumb>::new(Vec2::new(400., 0.)).apply(&mut app.world);
+    // Enemies walk right through crumbs
+    spawn_enemy(&mut app, Vec2::new(420., 0.), EnemyKind::Melee);
+
+    step(&mut app, 30);
+
+    assert_eq!(count::<Magnetized>(&mut app), 0);
+    assert_eq!(
+        crumb_positions::<XpCrumb>(&mut app),
+        vec![Vec2::new(400., 0.)]
+    );
+}
+
+#[test]
+fn magnets_vacuum_every_crumb() {
+    let mut app = headless_app();
+    let player = player(&mut app);
+    TakeDamage::<Player>::new(10.).apply(player, &mut app.world);
+    let damaged = health(&app, player).cur_hp;
+
+    SpawnCrumb::<XpCrumb>::new(Vec2::new(1000., 0.)).apply(&mut app.world);
+    SpawnCrumb::<XpCrumb>::new(Vec2::new(-800., 600.)).apply(&mut app.world);
+    SpawnCrumb::<HealingCrumb>::new(Vec2::new(0., -1200.)).apply(&mut app.world);
+    SpawnCrumb::<MagnetCrumb>::new(Vec2::new(0., 50.)).apply(&mut app.world);
+
+    step(&mut app, 30);
+    assert_eq!(count::<MagnetCrumb>(&mut app), 0);
+    assert_eq!(count::<Magnetized>(&mut app), 3);
+
+    step(&mut app, 180);
+    assert_eq!(count::<XpCrumb>(&mut app), 0);
+    assert_eq!(count::<HealingCrumb>(&mut app), 0);
+    assert!(health(&app, player).cur_hp > damaged);
+}
+
+#[test]
+fn ending_the_run_removes_every_crumb() {
+    let mut app = headless_app();
+    SpawnCrumb::<XpCrumb>::new(Vec2::new(600., 0.)).apply(&mut app.world);
+    SpawnCrumb::<HealingCrumb>::new(Vec2::new(0., 600.)).apply(&mut app.world);
+    SpawnCrumb::<MagnetCrumb>::new(Vec2::new(-600., 0.)).apply(&mut app.world);
+
+    EndRun.apply(&mut app.world);
+    assert_eq!(count::<Crumb>(&mut app), 0);
+}
@@ -0,0 +1,165 @@
+mod common;
+
+use bevy::{
+    ecs::system::{Command, EntityCommand},
+    prelude::*,
+};
+use bevy_physics_playground::{
+    blink::{Blink, StartBlinking},
+    director::SpawnDirector,
+    enemy::EnemyKind,
+    hurtbox::{GoInvulnerable, Heal, Invulnerable, TakeDamage},
+    prelude::*,
+    stats::RunStats,
+    weapon::{Weapon, CRIT_MULTIPLIER},
+    xp_crumbs::{Crumb, HealingCrumb, PlayerLevel, SpawnCrumb, XpCrumb},
+};
+use common::{
+    count, edit_player_weapon, headless_app, health, player, shoot_until_hit, spawn_enemy, step,
+};
+
+#[test]
+fn projectile_hits_reduce_enemy_health() {
+    let mut app = headless_app();
+    let enemy = spawn_enemy(&mut app, Vec2::new(150., 0.), EnemyKind::Melee);
+
+    assert!(shoot_until_hit(&mut app, enemy));
+}
+
+#[test]
+fn critical_hits_multiply_the_damage() {
+    let mut app = headless_app();
+    let enemy = spawn_enemy(&mut app, Vec2::new(150., 0.), EnemyKind::Melee);
+    let max_hp = health(&app, enemy).max_hp;
+    edit_player_weapon(&mut app, |weapon| weapon.crit_chance = 1.);
+
+    // Only the first hit, the enemy doesn't survive a second one
+    assert!(shoot_until_hit(&mut app, enemy));
+    assert_eq!(
+        health(&app, enemy).cur_hp,
+        max_hp - Weapon::pistol().damage * CRIT_MULTIPLIER
+    );
+}
+
+#[test]
+fn dead_enemies_drop_a_crumb() {
+    let mut app = headless_app();
+    let enemy = spawn_enemy(&mut app, Vec2::new(300., 0.), EnemyKind::Melee);
+
+    TakeDamage::<Enemy>::new(1000.).apply(enemy, &mut app.world);
+    step(&mut app, 2);
+
+    assert!(app.world.get_entity(enemy).is_none());
+    assert_eq!(count::<Crumb>(&mut app), 1);
+}
+
+#[test]
+fn collecting_xp_levels_up() {
+    let mut app = headless_app();
+    let threshold = app.world.resource::<PlayerLevel>().next_level_threshold;
+    let xp_value = 5.;
+    let crumbs = (threshold / xp_value).ceil() as u32;
+
+    (0..crumbs).for_each(|idx| {
+        SpawnCrumb::<XpCrumb>::new(Vec2::new(20. * idx as f32, 50.)).apply(&mut app.world);
+    });
+    // Give the crumbs time to fly in from the collection radius
+    step(&mut app, 60);
+
+    assert_eq!(count::<XpCrumb>(&mut app), 0);
+    assert_eq!(app.world.resource::<PlayerLevel>().cur_level, 2);
+}
+
+#[test]
+fn healing_crumbs_heal_the_player() {
+    let mut app = headless_app();
+    let player = player(&mut app);
+
+    TakeDamage::<Player>::new(10.).apply(player, &mut app.world);
+    let damaged = health(&app, player).cur_hp;
+
+    SpawnCrumb::<HealingCrumb>::new(Vec2::ZERO).apply(&mut app.world);
+    step(&mut app, 5);
+
+    assert_eq!(count::<HealingCrumb>(&mut app), 0);
+    assert!(health(&app, player).cur_hp > damaged);
+}
+
+#[test]
+fn healing_never_exceeds_max_health() {
+    let mut app = headless_app();
+    let player = player(&mut app);
+
+    Heal::<Player>::new(100.).apply(player, &mut app.world);
+
+    let health = health(&app, player);
+    assert_eq!(health.cur_hp, health.max_hp);
+}
+
+#[test]
+fn invulnerability_expires_on_its_own() {
+    let mut app = headless_app();
+    let player = player(&mut app);
+
+    GoInvulnerable::new(0.5).apply(player, &mut app.world);
+    step(&mut app, 20);
+    assert!(app.world.get::<Invulnerable>(player).is_some());
+    assert!(app.world.get::<Blink>(player).is_none());
+
+    step(&mut app, 20);
+    assert!(app.world.get::<Invulnerable>(player).is_none());
+}
+
+#[test]
+fn blinking_restores_translucent_sprites() {
+    let mut app = headless_app();
+    let sprite = app
+        .world
+        .spawn(Sprite {
+            color: Color::WHITE.with_a(0.5),
+            ..Default::default()
+        })
+        .id();
+
+    StartBlinking::new(0.5, 3).apply(sprite, &mut app.world);
+    step(&mut app, 7);
+    assert!(app.world.get::<Invulnerable>(sprite).is_none());
+    assert_eq!(app.world.get::<Sprite>(sprite).unwrap().color.a(), 0.);
+
+    // Starting over mid-blink keeps the original alpha
+    StartBlinking::new(0.5, 3).apply(sprite, &mut app.world);
+    step(&mut app, 40);
+    assert!(app.world.get::<Blink>(sprite).is_none());
+    assert_eq!(app.world.get::<Sprite>(sprite).unwrap().color.a(), 0.5);
+}
+
+#[test]
+fn the_director_opens_the_run() {
+    let mut app = headless_app();
+    app.insert_resource(SpawnDirector::default());
+
+    step(&mut app, 30);
+    assert_eq!(count::<Enemy>(&mut app), 0);
+
+    step(&mut app, 60);
+    assert!(count::<Enemy>(&mut app) > 0);
+}
+
+#[test]
+fn damage_taken_adds_up_the_hits() {
+    let mut app = headless_app();
+    let player = player(&mut app);
+
+    // A heal in the same tick doesn't hide the hit
+    TakeDamage::<Player>::new(4.).apply(player, &mut app.world);
+    Heal::<Player>::new(4.).apply(player, &mut app.world);
+    step(&mut app, 1);
+    // Health lost without a hit, like a reload lowering the maximum, isn't damage
+    app.world
+        .get_mut::<Health>(player)
+        .expect("Player does not have health")
+        .cur_hp -= 5.;
+    step(&mut app, 1);
+
+    assert_eq!(app.world.resource::<RunStats>().damage_taken, 4.);
+}
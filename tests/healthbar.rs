@@ -0,0 +1,59 @@
+mod common;
+
+use bevy::prelude::*;
+use bevy_physics_playground::{
+    enemy::EnemyKind,
+    healthbar::{HealthbarBackground, HealthbarForeground},
+    prelude::*,
+};
+use common::{count, headless_app, spawn_enemy, step};
+
+fn healthbar_app() -> App {
+    let mut app = headless_app();
+    app.add_plugins(AssetPlugin::default())
+        .init_asset::<Mesh>()
+        .init_asset::<ColorMaterial>()
+        .add_plugins(HealthbarPlugin);
+    app
+}
+
+fn asset_counts(app: &App) -> (usize, usize) {
+    (
+        app.world.resource::<Assets<Mesh>>().len(),
+        app.world.resource::<Assets<ColorMaterial>>().len(),
+    )
+}
+
+#[test]
+fn health_changes_reuse_the_bar_assets() {
+    let mut app = healthbar_app();
+    let enemy = spawn_enemy(&mut app, Vec2::new(600., 0.), EnemyKind::Melee);
+    step(&mut app, 1);
+    let assets = asset_counts(&app);
+
+    (0..50).for_each(|tick| {
+        let mut health = app
+            .world
+            .get_mut::<Health>(enemy)
+            .expect("Enemy does not have health");
+        health.cur_hp = health.max_hp * (tick % 10 + 1) as f32 / 10.;
+        step(&mut app, 1);
+    });
+
+    assert_eq!(asset_counts(&app), assets);
+}
+
+#[test]
+fn despawned_entities_leave_no_healthbar_behind() {
+    let mut app = healthbar_app();
+    let enemy = spawn_enemy(&mut app, Vec2::new(600., 0.), EnemyKind::Melee);
+    step(&mut app, 1);
+    let bars = count::<HealthbarBackground>(&mut app);
+
+    // Without its children, so the bar has to be found through its parent
+    app.world.despawn(enemy);
+    step(&mut app, 1);
+
+    assert_eq!(count::<HealthbarBackground>(&mut app), bars - 1);
+    assert_eq!(count::<HealthbarForeground>(&mut app), bars - 1);
+}
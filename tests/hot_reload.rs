@@ -0,0 +1,90 @@
+mod common;
+
+use bevy::{ecs::system::EntityCommand, prelude::*};
+use bevy_physics_playground::{
+    config::{ConfigAsset, ConfigReloaded, EnemyConfig, PlayerConfig},
+    enemy::EnemyKind,
+    hurtbox::TakeDamage,
+    knockback::KnockbackMass,
+    prelude::*,
+    upgrades::{ApplyUpgrade, Upgrade},
+};
+use common::{count, headless_app, health, player, spawn_enemy, step};
+
+/// Swaps in an edited config the way a file reload would.
+fn reload<T: ConfigAsset>(app: &mut App, edit: impl FnOnce(&mut T)) {
+    let mut config = app.world.resource::<T>().clone();
+    edit(&mut config);
+    app.world.insert_resource(config);
+    app.world.send_event(ConfigReloaded::<T>::default());
+    step(app, 1);
+}
+
+fn acceleration(app: &App, entity: Entity) -> f32 {
+    app.world
+        .get::<BaseAcceleration>(entity)
+        .expect("Entity does not have an acceleration")
+        .0
+}
+
+#[test]
+fn upgrades_survive_a_reload() {
+    let mut app = headless_app();
+    let player = player(&mut app);
+    ApplyUpgrade(Upgrade::MoveSpeed).apply(player, &mut app.world);
+    ApplyUpgrade(Upgrade::MaxHealth).apply(player, &mut app.world);
+
+    reload::<PlayerConfig>(&mut app, |config| {
+        config.movement.acceleration = 1000.;
+        config.health = 40.;
+    });
+
+    assert!((acceleration(&app, player) - 1100.).abs() < 0.01);
+    assert_eq!(health(&app, player).max_hp, 45.);
+}
+
+#[test]
+fn shrinking_health_below_one_keeps_the_player_alive() {
+    let mut app = headless_app();
+    let player = player(&mut app);
+
+    reload::<PlayerConfig>(&mut app, |config| config.health = 0.5);
+
+    let health = health(&app, player);
+    assert_eq!(health.max_hp, 0.5);
+    assert_eq!(health.cur_hp, 0.5);
+    assert_eq!(count::<Player>(&mut app), 1);
+}
+
+#[test]
+fn reloading_enemy_health_keeps_the_damage_taken() {
+    let mut app = headless_app();
+    let enemy = spawn_enemy(&mut app, Vec2::new(600., 0.), EnemyKind::Melee);
+    let max_hp = health(&app, enemy).max_hp;
+    TakeDamage::<Enemy>::new(max_hp / 2.).apply(enemy, &mut app.world);
+
+    reload::<EnemyConfig>(&mut app, |config| config.health = max_hp * 4.);
+
+    let health = health(&app, enemy);
+    assert_eq!(health.max_hp, max_hp * 4.);
+    assert_eq!(health.cur_hp, max_hp * 2.);
+}
+
+#[test]
+fn resizing_updates_the_knockback_mass() {
+    let mut app = headless_app();
+    let player = player(&mut app);
+    let enemy = spawn_enemy(&mut app, Vec2::new(600., 0.), EnemyKind::Melee);
+
+    reload::<PlayerConfig>(&mut app, |config| config.size = 64.);
+    reload::<EnemyConfig>(&mut app, |config| config.size = 16.);
+
+    let mass = |entity| {
+        app.world
+            .get::<KnockbackMass>(entity)
+            .expect("Entity does not have a knockback mass")
+            .0
+    };
+    assert_eq!(mass(player), KnockbackMass::from_size(64.).0);
+    assert_eq!(mass(enemy), KnockbackMass::from_size(16.).0);
+}
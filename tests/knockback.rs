@@ -0,0 +1,90 @@
+mod common;
+
+use bevy::{ecs::system::EntityCommand, prelude::*};
+use bevy_physics_playground::{
+    enemy::EnemyKind,
+    knockback::{Knockback, KnockbackMass, Stunned},
+};
+use bevy_xpbd_2d::prelude::*;
+use common::{headless_app, player, shoot_until_hit, spawn_enemy, step};
+
+fn velocity(app: &App, entity: Entity) -> Vec2 {
+    app.world
+        .get::<LinearVelocity>(entity)
+        .expect("Entity does not have a velocity")
+        .0
+}
+
+#[test]
+fn heavier_entities_are_pushed_less() {
+    let mut app = headless_app();
+    let light = spawn_enemy(&mut app, Vec2::new(600., 0.), EnemyKind::Melee);
+    let heavy = spawn_enemy(&mut app, Vec2::new(-600., 0.), EnemyKind::Melee);
+    app.world.entity_mut(heavy).insert(KnockbackMass(4.));
+    step(&mut app, 1);
+    let (light_before, heavy_before) = (velocity(&app, light).y, velocity(&app, heavy).y);
+
+    Knockback::new(Vec2::Y, 200.).apply(light, &mut app.world);
+    Knockback::new(Vec2::Y, 200.).apply(heavy, &mut app.world);
+
+    assert_eq!(velocity(&app, light).y - light_before, 200.);
+    assert_eq!(velocity(&app, heavy).y - heavy_before, 50.);
+}
+
+#[test]
+fn stunned_enemies_stop_steering() {
+    let mut app = headless_app();
+    let enemy = spawn_enemy(&mut app, Vec2::new(600., 0.), EnemyKind::Melee);
+    step(&mut app, 1);
+
+    Knockback::new(Vec2::X, 0.)
+        .with_stun(0.5)
+        .apply(enemy, &mut app.world);
+    step(&mut app, 10);
+    assert!(app.world.get::<Stunned>(enemy).is_some());
+    assert_eq!(velocity(&app, enemy), Vec2::ZERO);
+
+    step(&mut app, 30);
+    assert!(app.world.get::<Stunned>(enemy).is_none());
+    step(&mut app, 5);
+    assert!(velocity(&app, enemy).x < 0.);
+}
+
+#[test]
+fn shorter_stuns_do_not_cut_longer_ones() {
+    let mut app = headless_app();
+    let enemy = spawn_enemy(&mut app, Vec2::new(600., 0.), EnemyKind::Melee);
+
+    Knockback::new(Vec2::X, 0.)
+        .with_stun(1.)
+        .apply(enemy, &mut app.world);
+    Knockback::new(Vec2::X, 0.)
+        .with_stun(0.1)
+        .apply(enemy, &mut app.world);
+    step(&mut app, 30);
+
+    assert!(app.world.get::<Stunned>(enemy).is_some());
+}
+
+#[test]
+fn projectile_hits_push_and_stun_enemies() {
+    let mut app = headless_app();
+    let enemy = spawn_enemy(&mut app, Vec2::new(150., 0.), EnemyKind::Melee);
+
+    assert!(shoot_until_hit(&mut app, enemy));
+    assert!(app.world.get::<Stunned>(enemy).is_some());
+}
+
+#[test]
+fn enemy_contact_pushes_the_player_away() {
+    let mut app = headless_app();
+    let player = player(&mut app);
+    spawn_enemy(&mut app, Vec2::new(40., 0.), EnemyKind::Melee);
+
+    let pushed = (0..30).any(|_| {
+        step(&mut app, 1);
+        velocity(&app, player).x < -50.
+    });
+
+    assert!(pushed);
+}
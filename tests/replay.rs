@@ -0,0 +1,100 @@
+use bevy::prelude::*;
+use bevy_physics_playground::{
+    director::SpawnDirector,
+    game_state::GameState,
+    headless::ScriptedInput,
+    prelude::*,
+    replay::{Replay, ReplayPlayback, ReplayRecorder},
+    rng::RunSeed,
+    stats::RunStats,
+};
+
+fn replay_app(seed: u64) -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        HeadlessPlugin,
+        SimulationPlugin,
+        ReplayPlugin,
+    ))
+    .insert_resource(RunSeed(Some(seed)));
+    app
+}
+
+/// Circles around while shooting at the closest enemy, so that both the movement and the aim
+/// change every tick. Set from outside the schedule to keep the recording and playback apps alike.
+fn steer(app: &mut App, tick: u32) {
+    let player_pos = app.world.resource::<PlayerPosition>().0;
+    let nearest = app
+        .world
+        .query_filtered::<&Transform, With<Enemy>>()
+        .iter(&app.world)
+        .map(|transform| transform.translation.truncate() - player_pos)
+        .min_by(|a, b| a.length_squared().total_cmp(&b.length_squared()));
+
+    *app.world.resource_mut::<ScriptedInput>() = ScriptedInput {
+        movement: Vec2::from_angle(tick as f32 / 60.),
+        shoot: nearest.is_some(),
+        aim: nearest.unwrap_or(Vec2::X),
+        ..Default::default()
+    };
+}
+
+/// Player position and health, and the kills and damage taken so far.
+fn run_state(app: &mut App) -> (Vec3, Health, u32, f32) {
+    let (transform, health) = app
+        .world
+        .query_filtered::<(&Transform, &Health), With<Player>>()
+        .single(&app.world);
+    let (position, health) = (transform.translation, *health);
+    let stats = app.world.resource::<RunStats>();
+    (position, health, stats.kills, stats.damage_taken)
+}
+
+#[test]
+fn a_recorded_run_plays_back_identically() {
+    let path = std::env::temp_dir().join(format!("replay-test-{}.ron", std::process::id()));
+
+    let mut recording = replay_app(11);
+    recording.insert_resource(ReplayRecorder::new(path.clone()));
+    (0..2400).for_each(|tick| {
+        steer(&mut recording, tick);
+        recording.update();
+    });
+    assert!(
+        recording.world.get_resource::<SpawnDirector>().is_some(),
+        "The recorded run never started"
+    );
+    let recorded = run_state(&mut recording);
+    recording
+        .world
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::MainMenu);
+    recording.update();
+
+    let replay = Replay::load(path.to_str().expect("Temp path is not valid UTF-8"))
+        .expect("Failed to load the recorded replay");
+    std::fs::remove_file(&path).expect("Failed to remove the recorded replay");
+    assert_eq!(replay.seed, 11);
+
+    let mut playback = replay_app(replay.seed);
+    playback.insert_resource(ReplayPlayback::new(replay));
+    for _ in 0..5000 {
+        if !playback.world.contains_resource::<ReplayPlayback>() {
+            break;
+        }
+        playback.update();
+    }
+    assert!(
+        !playback.world.contains_resource::<ReplayPlayback>(),
+        "The replay never finished"
+    );
+
+    let (position, health, kills, damage_taken) = run_state(&mut playback);
+    assert!(recorded.2 > 0, "The recorded run never hit anything");
+    assert_eq!(position, recorded.0);
+    assert_eq!(health.cur_hp, recorded.1.cur_hp);
+    assert_eq!(health.max_hp, recorded.1.max_hp);
+    assert_eq!(kills, recorded.2);
+    assert_eq!(damage_taken, recorded.3);
+}
@@ -0,0 +1,150 @@
+mod common;
+
+use bevy::{ecs::system::EntityCommand, prelude::*};
+use bevy_physics_playground::{
+    config::{ConfigReloaded, EnemyConfig},
+    enemy::EnemyKind,
+    prelude::*,
+    status_effects::{
+        ApplyStatusEffect, StatusEffect, StatusEffectKind, StatusEffects, StatusImmunities,
+    },
+};
+use common::{
+    edit_player_weapon, headless_app, health, player, shoot_until_hit, spawn_enemy, step,
+};
+
+fn acceleration(app: &App, entity: Entity) -> f32 {
+    app.world
+        .get::<MovementAcceleration>(entity)
+        .expect("Entity does not have an acceleration")
+        .0
+}
+
+fn effect_count(app: &App, entity: Entity, kind: StatusEffectKind) -> usize {
+    app.world
+        .get::<StatusEffects>(entity)
+        .map_or(0, |effects| effects.count(kind))
+}
+
+#[test]
+fn burn_damages_over_time() {
+    let mut app = headless_app();
+    let enemy = spawn_enemy(&mut app, Vec2::new(600., 0.), EnemyKind::Melee);
+    let max_hp = health(&app, enemy).max_hp;
+
+    ApplyStatusEffect(StatusEffect::burn()).apply(enemy, &mut app.world);
+    step(&mut app, 20);
+    assert_eq!(health(&app, enemy).cur_hp, max_hp);
+
+    step(&mut app, 20);
+    assert_eq!(health(&app, enemy).cur_hp, max_hp - 1.);
+
+    step(&mut app, 160);
+    assert!(app.world.get::<StatusEffects>(enemy).is_none());
+    assert_eq!(health(&app, enemy).cur_hp, max_hp - 6.);
+}
+
+#[test]
+fn slow_wears_off() {
+    let mut app = headless_app();
+    let enemy = spawn_enemy(&mut app, Vec2::new(600., 0.), EnemyKind::Melee);
+    let base = acceleration(&app, enemy);
+
+    ApplyStatusEffect(StatusEffect::slow()).apply(enemy, &mut app.world);
+    step(&mut app, 2);
+    assert_eq!(acceleration(&app, enemy), base * 0.5);
+
+    step(&mut app, 150);
+    assert_eq!(acceleration(&app, enemy), base);
+}
+
+#[test]
+fn freeze_recovers_full_acceleration() {
+    let mut app = headless_app();
+    let enemy = spawn_enemy(&mut app, Vec2::new(600., 0.), EnemyKind::Melee);
+    let base = acceleration(&app, enemy);
+
+    ApplyStatusEffect(StatusEffect::freeze()).apply(enemy, &mut app.world);
+    step(&mut app, 2);
+    assert_eq!(acceleration(&app, enemy), 0.);
+
+    step(&mut app, 70);
+    assert_eq!(acceleration(&app, enemy), base);
+}
+
+#[test]
+fn immunities_block_effects() {
+    let mut app = headless_app();
+    let enemy = spawn_enemy(&mut app, Vec2::new(600., 0.), EnemyKind::Melee);
+    app.world
+        .entity_mut(enemy)
+        .insert(StatusImmunities(vec![StatusEffectKind::Burn]));
+
+    ApplyStatusEffect(StatusEffect::burn()).apply(enemy, &mut app.world);
+    ApplyStatusEffect(StatusEffect::poison()).apply(enemy, &mut app.world);
+
+    assert_eq!(effect_count(&app, enemy, StatusEffectKind::Burn), 0);
+    assert_eq!(effect_count(&app, enemy, StatusEffectKind::Poison), 1);
+}
+
+#[test]
+fn stacking_rules() {
+    let mut app = headless_app();
+    let enemy = spawn_enemy(&mut app, Vec2::new(600., 0.), EnemyKind::Melee);
+
+    (0..8).for_each(|_| {
+        ApplyStatusEffect(StatusEffect::poison()).apply(enemy, &mut app.world);
+        ApplyStatusEffect(StatusEffect::burn()).apply(enemy, &mut app.world);
+        ApplyStatusEffect(StatusEffect::freeze()).apply(enemy, &mut app.world);
+    });
+
+    assert_eq!(effect_count(&app, enemy, StatusEffectKind::Poison), 5);
+    assert_eq!(effect_count(&app, enemy, StatusEffectKind::Burn), 1);
+    assert_eq!(effect_count(&app, enemy, StatusEffectKind::Freeze), 1);
+}
+
+#[test]
+fn projectiles_apply_their_on_hit_effect() {
+    let mut app = headless_app();
+    let enemy = spawn_enemy(&mut app, Vec2::new(150., 0.), EnemyKind::Melee);
+    edit_player_weapon(&mut app, |weapon| {
+        weapon.on_hit = Some(StatusEffect::slow())
+    });
+
+    assert!(shoot_until_hit(&mut app, enemy));
+    assert_eq!(effect_count(&app, enemy, StatusEffectKind::Slow), 1);
+}
+
+#[test]
+fn slows_apply_on_top_of_a_config_reload() {
+    let mut app = headless_app();
+    let enemy = spawn_enemy(&mut app, Vec2::new(600., 0.), EnemyKind::Melee);
+    ApplyStatusEffect(StatusEffect::slow()).apply(enemy, &mut app.world);
+    step(&mut app, 2);
+
+    let mut config = app.world.resource::<EnemyConfig>().clone();
+    config.movement.acceleration = 1000.;
+    app.world.insert_resource(config);
+    app.world
+        .send_event(ConfigReloaded::<EnemyConfig>::default());
+    step(&mut app, 2);
+    assert_eq!(acceleration(&app, enemy), 500.);
+
+    step(&mut app, 150);
+    assert_eq!(acceleration(&app, enemy), 1000.);
+}
+
+#[test]
+fn enemy_bolts_slow_the_player() {
+    let mut app = headless_app();
+    let player = player(&mut app);
+    spawn_enemy(&mut app, Vec2::new(300., 0.), EnemyKind::Ranged);
+
+    for _ in 0..300 {
+        if effect_count(&app, player, StatusEffectKind::Slow) > 0 {
+            break;
+        }
+        step(&mut app, 1);
+    }
+    assert_eq!(effect_count(&app, player, StatusEffectKind::Slow), 1);
+}